@@ -10,7 +10,11 @@ use syn::{Expr, LitStr};
 /// [`SupportedFields`]: ./struct.SupportedFields.html
 #[proc_macro]
 pub fn arcdps_export(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let input = syn::parse_macro_input!(item as parse::ArcDpsGen);
+    let mut input = syn::parse_macro_input!(item as parse::ArcDpsGen);
+    let plugin = input
+        .plugin
+        .take()
+        .map(|plugin| build_plugin(&mut input, plugin));
     let sig = input.sig;
     let build = std::env::var("CARGO_PKG_VERSION").expect("CARGO_PKG_VERSION is not set") + "\0";
     let build = LitStr::new(build.as_str(), Span::call_site());
@@ -25,9 +29,18 @@ pub fn arcdps_export(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let out_name = raw_name + "\0";
     let out_name = LitStr::new(out_name.as_str(), span);
 
-    let (abstract_combat, cb_combat) = build_combat(input.raw_combat, input.combat);
-    let (abstract_combat_local, cb_combat_local) =
-        build_combat_local(input.raw_combat_local, input.combat_local);
+    let (abstract_combat, cb_combat) = build_combat(
+        input.raw_combat,
+        input.combat,
+        input.combat_channel,
+        input.plugin_message.clone(),
+    );
+    let (abstract_combat_local, cb_combat_local) = build_combat_local(
+        input.raw_combat_local,
+        input.combat_local,
+        input.combat_local_channel,
+        input.plugin_message,
+    );
     let (abstract_imgui, cb_imgui) = build_imgui(input.raw_imgui, input.imgui);
     let (abstract_options_end, cb_options_end) =
         build_options_end(input.raw_options_end, input.options_end);
@@ -57,14 +70,15 @@ pub fn arcdps_export(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
 
     let init = if let Some(init) = input.init {
         let span = syn::Error::new_spanned(&init, "").span();
-        quote_spanned! (span => unsafe { (#init as InitFunc)(__SWAPCHAIN) })
+        quote_spanned! (span => ::arcdps::__catch_panic("init", || (#init as InitFunc)(::arcdps::__swapchain()))
+            .unwrap_or_else(|| Err("init panicked or the plugin is poisoned".into())))
     } else {
         quote! {Ok(())}
     };
 
     let release = if let Some(release) = input.release {
         let span = syn::Error::new_spanned(&release, "").span();
-        quote_spanned! (span => (#release as ReleaseFunc)())
+        quote_spanned! (span => ::arcdps::__catch_panic("release", || (#release as ReleaseFunc)());)
     } else {
         quote! {}
     };
@@ -81,12 +95,24 @@ pub fn arcdps_export(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
         input.raw_unofficial_extras_chat_message2,
         input.unofficial_extras_chat_message2,
     );
+    let (abstract_extras_language_changed, extras_language_changed) = build_extras_language_changed(
+        input.raw_unofficial_extras_language_changed,
+        input.unofficial_extras_language_changed,
+    );
+    let (abstract_extras_keybind_changed, extras_keybind_changed) = build_extras_keybind_changed(
+        input.raw_unofficial_extras_keybind_changed,
+        input.unofficial_extras_keybind_changed,
+    );
     let abstract_extras_init = build_extras_init(
         input.raw_unofficial_extras_init,
         input.unofficial_extras_init,
-        extras_squad_update,
-        extras_chat_message,
-        extras_chat_message2,
+        ExtrasCallbacks {
+            squad_update: extras_squad_update,
+            chat_message: extras_chat_message,
+            chat_message2: extras_chat_message2,
+            language_changed: extras_language_changed,
+            keybind_changed: extras_keybind_changed,
+        },
         &out_name,
     );
 
@@ -105,18 +131,10 @@ pub fn arcdps_export(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
             __mallocfn: Option<unsafe extern "C" fn(sz: usize, user_data: *mut c_void) -> *mut c_void>,
             __freefn: Option<unsafe extern "C" fn(ptr: *mut c_void, user_data: *mut c_void)>,
         ) -> unsafe extern "system" fn() -> *const ArcDpsExport {
-            imgui::sys::igSetCurrentContext(__imguictx);
-            imgui::sys::igSetAllocatorFunctions(__mallocfn, __freefn, ::core::ptr::null_mut());
-            __CTX = Some(imgui::Context::current());
-            let __ctx = &raw const __CTX;
-            __UI = Some(imgui::Ui::from_ctx((*__ctx).as_ref().unwrap()));
-            __SWAPCHAIN = NonNull::new(__id3dptr);
-            ::arcdps::__init(__arc_version, __arc_dll, #name);
+            ::arcdps::__init_imgui(__imguictx, __mallocfn, __freefn);
+            ::arcdps::__init(__arc_version, __arc_dll, __id3dptr, __EXPORT.sig, #name);
             __load
         }
-
-        static mut __CTX: Option<imgui::Context> = None;
-        static mut __UI: Option<imgui::Ui> = None;
     };
 
     #[cfg(not(feature = "imgui"))]
@@ -132,8 +150,7 @@ pub fn arcdps_export(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
             _mallocfn: Option<unsafe extern "C" fn(sz: usize, user_data: *mut c_void) -> *mut c_void>,
             _freefn: Option<unsafe extern "C" fn(ptr: *mut c_void, user_data: *mut c_void)>,
         ) -> unsafe extern "system" fn() -> *const ArcDpsExport {
-            __SWAPCHAIN = NonNull::new(__id3dptr);
-            ::arcdps::__init(__arc_version, __arc_dll, #name);
+            ::arcdps::__init(__arc_version, __arc_dll, __id3dptr, __EXPORT.sig, #name);
             __load
         }
     };
@@ -146,6 +163,7 @@ pub fn arcdps_export(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
             use ::arcdps::ArcDpsExport;
             use ::arcdps::{InitFunc, ReleaseFunc};
 
+            #plugin
             #abstract_combat
             #abstract_combat_local
             #abstract_imgui
@@ -156,42 +174,23 @@ pub fn arcdps_export(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
             #abstract_extras_squad_update
             #abstract_extras_chat_message
             #abstract_extras_chat_message2
+            #abstract_extras_language_changed
+            #abstract_extras_keybind_changed
             #abstract_extras_init
 
             static __EXPORT: ArcDpsExport = #export;
-            static mut __EXPORT_ERROR: ArcDpsExport = ArcDpsExport {
-                    size: 0,
-                    sig: 0,
-                    imgui_version: 18000,
-                    out_build: #build.as_ptr(),
-                    out_name: #out_name.as_ptr(),
-                    combat: None,
-                    combat_local: None,
-                    imgui: None,
-                    options_end: None,
-                    options_windows: None,
-                    wnd_filter: None,
-                    wnd_nofilter: None,
-                };
-            static mut __ERROR_STRING: String = String::new();
-            static mut __SWAPCHAIN: Option<NonNull<c_void>> = None;
 
             unsafe extern "system" fn __load() -> *const ArcDpsExport {
-                let mut __export = &raw const __EXPORT;
                 let __res: Result<(), Box<dyn ::std::error::Error>> = #init;
-                if let Err(__e) = __res {
-                    unsafe {
-                        __ERROR_STRING = __e.to_string() + "\0";
-                        __EXPORT_ERROR.size = &raw const __ERROR_STRING as _;
-                        __export = &raw const __EXPORT_ERROR;
-                    }
+                match __res {
+                    Ok(()) => &__EXPORT,
+                    Err(__e) => ::arcdps::__init_failed(&__EXPORT, __e.as_ref()),
                 }
-
-                __export
             }
 
             unsafe extern "system" fn __unload() {
                 #release
+                ::arcdps::__release();
             }
 
             #sys_init
@@ -206,6 +205,148 @@ pub fn arcdps_export(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
     res.into()
 }
 
+/// Generates the glue that forwards all callbacks to a type implementing
+/// `arcdps::Plugin` and registers it in place of the user provided callbacks.
+fn build_plugin(input: &mut parse::ArcDpsGen, plugin: Expr) -> TokenStream {
+    input.init = Some(syn::parse_quote!(__plugin_init));
+    input.release = Some(syn::parse_quote!(__plugin_release));
+    input.combat = Some(syn::parse_quote!(__plugin_combat));
+    input.combat_local = Some(syn::parse_quote!(__plugin_combat_local));
+    input.plugin_message = Some(syn::parse_quote!(__plugin_message));
+    input.wnd_filter = Some(syn::parse_quote!(__plugin_wnd_filter));
+    input.wnd_nofilter = Some(syn::parse_quote!(__plugin_wnd_nofilter));
+    input.unofficial_extras_init = Some(syn::parse_quote!(__plugin_extras_init));
+    input.unofficial_extras_squad_update = Some(syn::parse_quote!(__plugin_extras_squad_update));
+    input.unofficial_extras_chat_message = Some(syn::parse_quote!(__plugin_extras_chat_message));
+    input.unofficial_extras_chat_message2 = Some(syn::parse_quote!(__plugin_extras_chat_message2));
+    input.unofficial_extras_language_changed =
+        Some(syn::parse_quote!(__plugin_extras_language_changed));
+    input.unofficial_extras_keybind_changed =
+        Some(syn::parse_quote!(__plugin_extras_keybind_changed));
+
+    input.imgui = Some(syn::parse_quote!(__plugin_imgui));
+    input.options_end = Some(syn::parse_quote!(__plugin_options_end));
+    input.options_windows = Some(syn::parse_quote!(__plugin_options_windows));
+
+    #[cfg(feature = "imgui")]
+    let ui = quote! {
+        fn __plugin_imgui(__ui: &::arcdps::imgui::Ui, __not_loading: bool) {
+            __PLUGIN.with(|__p| ::arcdps::Plugin::imgui(__p, __ui, __not_loading));
+        }
+
+        fn __plugin_options_end(__ui: &::arcdps::imgui::Ui) {
+            __PLUGIN.with(|__p| ::arcdps::Plugin::options_end(__p, __ui));
+        }
+
+        fn __plugin_options_windows(__ui: &::arcdps::imgui::Ui, __window_name: Option<&str>) -> bool {
+            __PLUGIN
+                .with(|__p| ::arcdps::Plugin::options_windows(__p, __ui, __window_name))
+                .unwrap_or(false)
+        }
+    };
+    #[cfg(not(feature = "imgui"))]
+    let ui = quote! {
+        fn __plugin_imgui(__not_loading: bool) {
+            __PLUGIN.with(|__p| ::arcdps::Plugin::imgui(__p, __not_loading));
+        }
+
+        fn __plugin_options_end() {
+            __PLUGIN.with(|__p| ::arcdps::Plugin::options_end(__p));
+        }
+
+        fn __plugin_options_windows(__window_name: Option<&str>) -> bool {
+            __PLUGIN
+                .with(|__p| ::arcdps::Plugin::options_windows(__p, __window_name))
+                .unwrap_or(false)
+        }
+    };
+
+    let span = syn::Error::new_spanned(&plugin, "").span();
+    quote_spanned! {span =>
+        static __PLUGIN: ::arcdps::PluginHolder<#plugin> = ::arcdps::PluginHolder::new();
+
+        fn __plugin_init(
+            __swapchain: Option<NonNull<c_void>>,
+        ) -> Result<(), Box<dyn ::std::error::Error>> {
+            __PLUGIN.init(__swapchain)
+        }
+
+        fn __plugin_release() {
+            __PLUGIN.release()
+        }
+
+        fn __plugin_combat(
+            __ev: Option<&::arcdps::CombatEvent>,
+            __src: Option<::arcdps::Agent>,
+            __dst: Option<::arcdps::Agent>,
+            __skill_name: Option<&'static str>,
+            __id: u64,
+            __revision: u64,
+        ) {
+            __PLUGIN.try_with(|__p| {
+                ::arcdps::Plugin::combat(__p, __ev, __src, __dst, __skill_name, __id, __revision)
+            });
+        }
+
+        fn __plugin_combat_local(
+            __ev: Option<&::arcdps::CombatEvent>,
+            __src: Option<::arcdps::Agent>,
+            __dst: Option<::arcdps::Agent>,
+            __skill_name: Option<&'static str>,
+            __id: u64,
+            __revision: u64,
+        ) {
+            __PLUGIN.try_with(|__p| {
+                ::arcdps::Plugin::combat_local(__p, __ev, __src, __dst, __skill_name, __id, __revision)
+            });
+        }
+
+        fn __plugin_message(__message: &::arcdps::PluginMessage) {
+            __PLUGIN.try_with(|__p| ::arcdps::Plugin::plugin_message(__p, __message));
+        }
+
+        fn __plugin_wnd_filter(__key: usize, __key_down: bool, __prev_key_down: bool) -> bool {
+            __PLUGIN
+                .with(|__p| ::arcdps::Plugin::wnd_filter(__p, __key, __key_down, __prev_key_down))
+                .unwrap_or(true)
+        }
+
+        fn __plugin_wnd_nofilter(__key: usize, __key_down: bool, __prev_key_down: bool) -> bool {
+            __PLUGIN
+                .with(|__p| ::arcdps::Plugin::wnd_nofilter(__p, __key, __key_down, __prev_key_down))
+                .unwrap_or(true)
+        }
+
+        fn __plugin_extras_init(__account_name: Option<&str>, __version: Option<&'static str>) {
+            __PLUGIN.with(|__p| {
+                ::arcdps::Plugin::unofficial_extras_init(__p, __account_name, __version)
+            });
+        }
+
+        fn __plugin_extras_squad_update(__users: ::arcdps::UserInfoIter) {
+            __PLUGIN.with(|__p| ::arcdps::Plugin::unofficial_extras_squad_update(__p, __users));
+        }
+
+        fn __plugin_extras_chat_message(__msg: &::arcdps::SquadMessageInfo) {
+            __PLUGIN.with(|__p| ::arcdps::Plugin::unofficial_extras_chat_message(__p, __msg));
+        }
+
+        fn __plugin_extras_chat_message2(__msg: &::arcdps::ChatMessageInfo2) {
+            __PLUGIN.with(|__p| ::arcdps::Plugin::unofficial_extras_chat_message2(__p, __msg));
+        }
+
+        fn __plugin_extras_language_changed(__language: ::arcdps::Language) {
+            __PLUGIN.with(|__p| ::arcdps::Plugin::unofficial_extras_language_changed(__p, __language));
+        }
+
+        fn __plugin_extras_keybind_changed(__changed: ::arcdps::KeyBindChanged) {
+            __PLUGIN.with(|__p| ::arcdps::Plugin::unofficial_extras_keybind_changed(__p, __changed));
+        }
+
+        #ui
+    }
+}
+
 fn build_extras_squad_update(
     raw: Option<Expr>,
     safe: Option<Expr>,
@@ -221,9 +362,11 @@ fn build_extras_squad_update(
             abstract_wrapper = quote_spanned!(span =>
             unsafe extern "C" fn __abstract_extras_squad_update(__users: *const ::arcdps::RawUserInfo, __count: u64) {
                 let _ = #safe as ::arcdps::ExtrasSquadUpdateCallback;
-                let __users = ::std::slice::from_raw_parts(__users, __count as _);
-                let __users = __users.iter().map(::arcdps::helpers::convert_extras_user as ::arcdps::UserConvert);
-                #safe(__users)
+                ::arcdps::__catch_panic("unofficial_extras_squad_update", || {
+                    let __users = ::std::slice::from_raw_parts(__users, __count as _);
+                    let __users = __users.iter().map(::arcdps::helpers::convert_extras_user as ::arcdps::UserConvert);
+                    #safe(__users)
+                });
             });
             Some(
                 quote_spanned!(span => Some(__arcdps_gen_export::__abstract_extras_squad_update as _) ),
@@ -249,8 +392,10 @@ fn build_extras_chat_message(
             abstract_wrapper = quote_spanned!(span =>
             unsafe extern "C" fn __abstract_extras_chat_message(__msg: *const ::arcdps::RawSquadMessageInfo) {
                 let _ = #safe as ::arcdps::ExtrasChatMessageCallback;
-                let __msg = ::arcdps::helpers::convert_extras_squad_chat_message(&*__msg);
-                #safe(&__msg)
+                ::arcdps::__catch_panic("unofficial_extras_chat_message", || {
+                    let __msg = ::arcdps::helpers::convert_extras_squad_chat_message(&*__msg);
+                    #safe(&__msg)
+                });
             });
             Some(
                 quote_spanned!(span => Some(__arcdps_gen_export::__abstract_extras_chat_message as _) ),
@@ -276,8 +421,10 @@ fn build_extras_chat_message2(
             abstract_wrapper = quote_spanned!(span =>
                 unsafe extern "C" fn __abstract_extras_chat_message2(__msg_type: ::arcdps::ChatMessageType, __msg: ::arcdps::RawChatMessageInfo2) {
                 let _ = #safe as ::arcdps::ExtrasChatMessage2Callback;
-                let __msg = ::arcdps::helpers::convert_extras_chat_message2(__msg_type, __msg);
-                #safe(&__msg)
+                ::arcdps::__catch_panic("unofficial_extras_chat_message2", || {
+                    let __msg = ::arcdps::helpers::convert_extras_chat_message2(__msg_type, __msg);
+                    #safe(&__msg)
+                });
             });
             Some(
                 quote_spanned!(span => Some(__arcdps_gen_export::__abstract_extras_chat_message2 as _) ),
@@ -288,18 +435,85 @@ fn build_extras_chat_message2(
     (abstract_wrapper, cb_safe)
 }
 
-fn build_extras_init(
+fn build_extras_language_changed(
     raw: Option<Expr>,
     safe: Option<Expr>,
+) -> (TokenStream, Option<TokenStream>) {
+    let mut abstract_wrapper = quote! {};
+    let cb_safe = match (raw, safe) {
+        (Some(raw), _) => {
+            let span = syn::Error::new_spanned(&raw, "").span();
+            Some(quote_spanned!(span => Some(#raw as _) ))
+        }
+        (_, Some(safe)) => {
+            let span = syn::Error::new_spanned(&safe, "").span();
+            abstract_wrapper = quote_spanned!(span =>
+            unsafe extern "C" fn __abstract_extras_language_changed(__language: ::arcdps::Language) {
+                let _ = #safe as ::arcdps::ExtrasLanguageChangedCallback;
+                ::arcdps::__catch_panic("unofficial_extras_language_changed", || #safe(__language));
+            });
+            Some(
+                quote_spanned!(span => Some(__arcdps_gen_export::__abstract_extras_language_changed as _) ),
+            )
+        }
+        _ => None,
+    };
+    (abstract_wrapper, cb_safe)
+}
+
+fn build_extras_keybind_changed(
+    raw: Option<Expr>,
+    safe: Option<Expr>,
+) -> (TokenStream, Option<TokenStream>) {
+    let mut abstract_wrapper = quote! {};
+    let cb_safe = match (raw, safe) {
+        (Some(raw), _) => {
+            let span = syn::Error::new_spanned(&raw, "").span();
+            Some(quote_spanned!(span => Some(#raw as _) ))
+        }
+        (_, Some(safe)) => {
+            let span = syn::Error::new_spanned(&safe, "").span();
+            abstract_wrapper = quote_spanned!(span =>
+            unsafe extern "C" fn __abstract_extras_keybind_changed(__changed: ::arcdps::RawKeyBindChanged) {
+                let _ = #safe as ::arcdps::ExtrasKeyBindChangedCallback;
+                if let Ok(__changed) = ::arcdps::KeyBindChanged::try_from(__changed) {
+                    ::arcdps::__catch_panic("unofficial_extras_keybind_changed", || #safe(__changed));
+                }
+            });
+            Some(
+                quote_spanned!(span => Some(__arcdps_gen_export::__abstract_extras_keybind_changed as _) ),
+            )
+        }
+        _ => None,
+    };
+    (abstract_wrapper, cb_safe)
+}
+
+/// The callbacks that get registered in the unofficial extras subscriber info.
+struct ExtrasCallbacks {
     squad_update: Option<TokenStream>,
     chat_message: Option<TokenStream>,
     chat_message2: Option<TokenStream>,
+    language_changed: Option<TokenStream>,
+    keybind_changed: Option<TokenStream>,
+}
+
+fn build_extras_init(
+    raw: Option<Expr>,
+    safe: Option<Expr>,
+    callbacks: ExtrasCallbacks,
     name: &LitStr,
 ) -> TokenStream {
-    let needs_init = squad_update.is_some() || chat_message.is_some();
-    let squad_cb = squad_update.unwrap_or(quote! { None });
-    let chat_cb = chat_message.unwrap_or(quote! { None });
-    let chat_cb2 = chat_message2.unwrap_or(quote! { None });
+    let needs_init = callbacks.squad_update.is_some()
+        || callbacks.chat_message.is_some()
+        || callbacks.chat_message2.is_some()
+        || callbacks.language_changed.is_some()
+        || callbacks.keybind_changed.is_some();
+    let squad_cb = callbacks.squad_update.unwrap_or(quote! { None });
+    let chat_cb = callbacks.chat_message.unwrap_or(quote! { None });
+    let chat_cb2 = callbacks.chat_message2.unwrap_or(quote! { None });
+    let language_cb = callbacks.language_changed.unwrap_or(quote! { None });
+    let keybind_cb = callbacks.keybind_changed.unwrap_or(quote! { None });
 
     let basic_init = quote!(
         if __addon.api_version != 2 {
@@ -315,8 +529,8 @@ fn build_extras_init(
 
             __sub.subscriber_name = #name.as_ptr();
             __sub.squad_update_callback = #squad_cb;
-            __sub.language_changed_callback = None;
-            __sub.key_bind_changed_callback = None;
+            __sub.language_changed_callback = #language_cb;
+            __sub.key_bind_changed_callback = #keybind_cb;
         }
 
         fn __fill_v2(__sub: *mut ::arcdps::RawExtrasSubscriberInfo<::arcdps::InfoV2>) {
@@ -359,11 +573,13 @@ fn build_extras_init(
                 #basic_init
 
                 let _ = #safe as ::arcdps::ExtrasInitFunc;
-                let __user = ::arcdps::helpers::get_str_from_pc_char(__addon.self_account_name as _)
-                                .map(|n| n.trim_start_matches(':'));
-                let __version = ::arcdps::helpers::get_str_from_pc_char(__addon.string_version as _);
+                ::arcdps::__catch_panic("unofficial_extras_init", || {
+                    let __user = ::arcdps::helpers::get_str_from_pc_char(__addon.self_account_name as _)
+                                    .map(|n| n.trim_start_matches(':'));
+                    let __version = ::arcdps::helpers::get_str_from_pc_char(__addon.string_version as _);
 
-                #safe(__user, __version)
+                    #safe(__user, __version)
+                });
             )
         }
         _ if needs_init => basic_init,
@@ -372,7 +588,7 @@ fn build_extras_init(
     use syn::spanned::Spanned;
     quote_spanned!(abstract_wrapper.span() =>
         #[no_mangle]
-        unsafe extern "system" fn arcdps_unofficial_extras_subscriber_init(
+        pub unsafe extern "system" fn arcdps_unofficial_extras_subscriber_init(
                                     __addon: &::arcdps::RawExtrasAddonInfo,
                                     __sub: *mut ::arcdps::RawExtrasSubscriberInfoHeader
         ) {
@@ -382,17 +598,23 @@ fn build_extras_init(
 }
 
 fn build_wnd_filter(raw_wnd: Option<Expr>, wnd: Option<Expr>) -> (TokenStream, TokenStream) {
-    build_wnd(raw_wnd, wnd, quote! { __abstract_wnd_filter })
+    build_wnd(raw_wnd, wnd, quote! { __abstract_wnd_filter }, "wnd_filter")
 }
 
 fn build_wnd_nofilter(raw_wnd: Option<Expr>, wnd: Option<Expr>) -> (TokenStream, TokenStream) {
-    build_wnd(raw_wnd, wnd, quote! { __abstract_wnd_nofilter })
+    build_wnd(
+        raw_wnd,
+        wnd,
+        quote! { __abstract_wnd_nofilter },
+        "wnd_nofilter",
+    )
 }
 
 fn build_wnd(
     raw_wnd_filter: Option<Expr>,
     wnd_filter: Option<Expr>,
     func_name: TokenStream,
+    callback: &str,
 ) -> (TokenStream, TokenStream) {
     let mut abstract_wnd_filter = quote! {};
     let cb_wnd_filter = match (raw_wnd_filter, wnd_filter) {
@@ -413,7 +635,8 @@ fn build_wnd(
                         let __key_down = __u_msg & 1 == 0;
                         let __prev_key_down = (__l_param >> 30) & 1 == 1;
 
-                        if #safe(__w_param, __key_down, __prev_key_down)
+                        if ::arcdps::__catch_panic(#callback, || #safe(__w_param, __key_down, __prev_key_down))
+                            .unwrap_or(true)
                         {
                             __u_msg
                         } else {
@@ -445,9 +668,13 @@ fn build_options_windows(
             abstract_options_windows = quote_spanned!(span =>
             unsafe extern "C" fn __abstract_options_windows(__window_name: *mut c_char) -> bool {
                 let _ = #safe as ::arcdps::OptionsWindowsCallback;
-                let __ui = &raw const __UI;
-                let __ui = (*__ui).as_ref().unwrap();
-                #safe(__ui, ::arcdps::helpers::get_str_from_pc_char(__window_name))
+                ::arcdps::__catch_panic("options_windows", || {
+                    let Some(__ui) = ::arcdps::__ui() else {
+                        return false;
+                    };
+                    #safe(__ui, ::arcdps::helpers::get_str_from_pc_char(__window_name))
+                })
+                .unwrap_or(false)
             });
             quote_spanned!(span => Some(__arcdps_gen_export::__abstract_options_windows as _) )
         }
@@ -471,9 +698,12 @@ fn build_options_end(
             abstract_options_end = quote_spanned!(span =>
             unsafe extern "C" fn __abstract_options_end() {
                 let _ = #safe as ::arcdps::OptionsCallback;
-                let __ui = &raw const __UI;
-                let __ui = (*__ui).as_ref().unwrap();
-                #safe(__ui)
+                ::arcdps::__catch_panic("options_end", || {
+                    let Some(__ui) = ::arcdps::__ui() else {
+                        return;
+                    };
+                    #safe(__ui)
+                });
             });
             quote_spanned!(span => Some(__arcdps_gen_export::__abstract_options_end as _) )
         }
@@ -494,9 +724,12 @@ fn build_imgui(raw_imgui: Option<Expr>, imgui: Option<Expr>) -> (TokenStream, To
             abstract_imgui = quote_spanned!(span =>
             unsafe extern "C" fn __abstract_imgui(__loading: u32) {
                 let _ = #safe as ::arcdps::ImguiCallback;
-                let __ui = &raw const __UI;
-                let __ui = (*__ui).as_ref().unwrap();
-                #safe(__ui, __loading != 0)
+                ::arcdps::__catch_panic("imgui", || {
+                    let Some(__ui) = ::arcdps::__ui() else {
+                        return;
+                    };
+                    #safe(__ui, __loading != 0)
+                });
             });
             quote_spanned!(span => Some(__arcdps_gen_export::__abstract_imgui as _) )
         }
@@ -508,43 +741,113 @@ fn build_imgui(raw_imgui: Option<Expr>, imgui: Option<Expr>) -> (TokenStream, To
 fn build_combat_local(
     raw_combat: Option<Expr>,
     combat: Option<Expr>,
+    channel: Option<Expr>,
+    message: Option<Expr>,
 ) -> (TokenStream, TokenStream) {
-    build_cbt(raw_combat, combat, quote! { __abstract_combat_local })
+    build_cbt(
+        raw_combat,
+        combat,
+        channel,
+        message,
+        false,
+        quote! { __abstract_combat_local },
+        "combat_local",
+    )
 }
 
-fn build_combat(raw_combat: Option<Expr>, combat: Option<Expr>) -> (TokenStream, TokenStream) {
-    build_cbt(raw_combat, combat, quote! { __abstract_combat })
+fn build_combat(
+    raw_combat: Option<Expr>,
+    combat: Option<Expr>,
+    channel: Option<Expr>,
+    message: Option<Expr>,
+) -> (TokenStream, TokenStream) {
+    build_cbt(
+        raw_combat,
+        combat,
+        channel,
+        message,
+        true,
+        quote! { __abstract_combat },
+        "combat",
+    )
 }
 
 fn build_cbt(
     raw_combat: Option<Expr>,
     combat: Option<Expr>,
+    channel: Option<Expr>,
+    message: Option<Expr>,
+    decode_messages: bool,
     func_name: TokenStream,
+    callback: &str,
 ) -> (TokenStream, TokenStream) {
-    let mut abstract_combat = quote! {};
-    let cb_combat = match (raw_combat, combat) {
-        (Some(raw), _) => {
+    // messages of other plugins are passed to their own callback. They are
+    // only decoded from the area combat, the local combat can carry the same
+    // events again.
+    let route = message.map(|message| {
+        let span = syn::Error::new_spanned(&message, "").span();
+        if decode_messages {
+            quote_spanned!(span =>
+                if ::arcdps::__route_message(__ev.as_deref(), #message as ::arcdps::PluginMessageCallback) {
+                    return;
+                }
+            )
+        } else {
+            quote_spanned!(span =>
+                if __ev.as_deref().is_some_and(::arcdps::is_message) {
+                    return;
+                }
+            )
+        }
+    });
+
+    let (span, call) = match (raw_combat, combat, channel) {
+        (Some(raw), ..) => {
             let span = syn::Error::new_spanned(&raw, "").span();
-            quote_spanned!(span => Some(#raw as _) )
+            return (quote! {}, quote_spanned!(span => Some(#raw as _) ));
         }
-        (_, Some(safe)) => {
+        (_, Some(safe), _) => {
             let span = syn::Error::new_spanned(&safe, "").span();
-            abstract_combat = quote_spanned!(span =>
-            unsafe extern "C" fn #func_name(
-                    __ev: Option<&::arcdps::CombatEvent>,
-                    __src: Option<&::arcdps::RawAgent>,
-                    __dst: Option<&::arcdps::RawAgent>,
-                    __skill_name: *mut c_char,
-                    __id: u64,
-                    __revision: u64,
-                ) {
-                    let _ = #safe as ::arcdps::CombatCallback;
-                    let __args = ::arcdps::helpers::get_combat_args_from_raw(__ev, __src, __dst, __skill_name);
-                    #safe(__args.ev, __args.src, __args.dst, __args.skill_name, __id, __revision)
-            });
-            quote_spanned!(span => Some(__arcdps_gen_export::#func_name as _) )
+            let call = quote_spanned!(span =>
+                let _ = #safe as ::arcdps::CombatCallback;
+                let __args = ::arcdps::helpers::get_combat_args_from_raw(__ev.as_deref(), __src, __dst, __skill_name);
+                #safe(__args.ev, __args.src, __args.dst, __args.skill_name, __id, __revision)
+            );
+            (span, call)
         }
-        _ => quote! { None },
+        (_, _, Some(channel)) => {
+            let span = syn::Error::new_spanned(&channel, "").span();
+            let call = quote_spanned!(span =>
+                let __args = ::arcdps::helpers::get_combat_args_from_raw(__ev.as_deref(), __src, __dst, __skill_name);
+                let __message = ::arcdps::CombatMessage::new(__args.ev, __args.src, __args.dst, __args.skill_name, __id, __revision);
+                ::arcdps::CombatChannel::push(&#channel, __message);
+            );
+            (span, call)
+        }
+        _ if route.is_some() && decode_messages => (
+            Span::call_site(),
+            quote! { let _ = (__src, __dst, __skill_name, __id); },
+        ),
+        _ => return (quote! {}, quote! { None }),
     };
+
+    let abstract_combat = quote_spanned!(span =>
+    unsafe extern "C" fn #func_name(
+            __ev: Option<&::arcdps::CombatEvent>,
+            __src: Option<&::arcdps::RawAgent>,
+            __dst: Option<&::arcdps::RawAgent>,
+            __skill_name: *mut c_char,
+            __id: u64,
+            __revision: u64,
+        ) {
+            ::arcdps::__catch_panic(#callback, || {
+                let Some(__ev) = ::arcdps::helpers::__normalize_combat_event(#callback, __ev, __revision) else {
+                    return;
+                };
+                #route
+                #call
+            });
+    });
+    let cb_combat = quote_spanned!(span => Some(__arcdps_gen_export::#func_name as _) );
     (abstract_combat, cb_combat)
 }
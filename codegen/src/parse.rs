@@ -44,6 +44,7 @@ pub(crate) struct ArcDpsGen {
     pub sig: Expr,
     pub init: Option<Expr>,
     pub release: Option<Expr>,
+    pub plugin: Option<Expr>,
     pub raw_wnd_nofilter: Option<Expr>,
     pub raw_imgui: Option<Expr>,
     pub raw_options_end: Option<Expr>,
@@ -55,6 +56,8 @@ pub(crate) struct ArcDpsGen {
     pub raw_unofficial_extras_squad_update: Option<Expr>,
     pub raw_unofficial_extras_chat_message: Option<Expr>,
     pub raw_unofficial_extras_chat_message2: Option<Expr>,
+    pub raw_unofficial_extras_language_changed: Option<Expr>,
+    pub raw_unofficial_extras_keybind_changed: Option<Expr>,
     pub wnd_nofilter: Option<Expr>,
     pub combat: Option<Expr>,
     pub imgui: Option<Expr>,
@@ -66,6 +69,51 @@ pub(crate) struct ArcDpsGen {
     pub unofficial_extras_squad_update: Option<Expr>,
     pub unofficial_extras_chat_message: Option<Expr>,
     pub unofficial_extras_chat_message2: Option<Expr>,
+    pub unofficial_extras_language_changed: Option<Expr>,
+    pub unofficial_extras_keybind_changed: Option<Expr>,
+    pub combat_channel: Option<Expr>,
+    pub combat_local_channel: Option<Expr>,
+    pub plugin_message: Option<Expr>,
+}
+
+impl ArcDpsGen {
+    fn has_callbacks(&self) -> bool {
+        [
+            &self.init,
+            &self.release,
+            &self.raw_wnd_nofilter,
+            &self.raw_imgui,
+            &self.raw_options_end,
+            &self.raw_combat,
+            &self.raw_wnd_filter,
+            &self.raw_options_windows,
+            &self.raw_combat_local,
+            &self.raw_unofficial_extras_init,
+            &self.raw_unofficial_extras_squad_update,
+            &self.raw_unofficial_extras_chat_message,
+            &self.raw_unofficial_extras_chat_message2,
+            &self.raw_unofficial_extras_language_changed,
+            &self.raw_unofficial_extras_keybind_changed,
+            &self.wnd_nofilter,
+            &self.combat,
+            &self.imgui,
+            &self.options_end,
+            &self.combat_local,
+            &self.wnd_filter,
+            &self.options_windows,
+            &self.unofficial_extras_init,
+            &self.unofficial_extras_squad_update,
+            &self.unofficial_extras_chat_message,
+            &self.unofficial_extras_chat_message2,
+            &self.unofficial_extras_language_changed,
+            &self.unofficial_extras_keybind_changed,
+            &self.combat_channel,
+            &self.combat_local_channel,
+            &self.plugin_message,
+        ]
+        .iter()
+        .any(|field| field.is_some())
+    }
 }
 
 impl syn::parse::Parse for ArcDpsGen {
@@ -78,6 +126,7 @@ impl syn::parse::Parse for ArcDpsGen {
 
             init: None,
             release: None,
+            plugin: None,
 
             combat: None,
             combat_local: None,
@@ -90,6 +139,8 @@ impl syn::parse::Parse for ArcDpsGen {
             unofficial_extras_squad_update: None,
             unofficial_extras_chat_message: None,
             unofficial_extras_chat_message2: None,
+            unofficial_extras_language_changed: None,
+            unofficial_extras_keybind_changed: None,
 
             raw_combat: None,
             raw_combat_local: None,
@@ -102,6 +153,12 @@ impl syn::parse::Parse for ArcDpsGen {
             raw_unofficial_extras_squad_update: None,
             raw_unofficial_extras_chat_message: None,
             raw_unofficial_extras_chat_message2: None,
+            raw_unofficial_extras_language_changed: None,
+            raw_unofficial_extras_keybind_changed: None,
+
+            combat_channel: None,
+            combat_local_channel: None,
+            plugin_message: None,
         };
 
         let mut sig_done = false;
@@ -139,6 +196,28 @@ impl syn::parse::Parse for ArcDpsGen {
                         gen.release = Some(field.expr);
                     }
 
+                    "plugin" => {
+                        if !matches!(field.expr, Expr::Path(_)) {
+                            return Err(Error::new_spanned(
+                                field.expr,
+                                "plugin needs to be the path of a type implementing arcdps::Plugin",
+                            ));
+                        }
+                        gen.plugin = Some(field.expr);
+                    }
+
+                    "combat_channel" => {
+                        gen.combat_channel = Some(field.expr);
+                    }
+
+                    "combat_local_channel" => {
+                        gen.combat_local_channel = Some(field.expr);
+                    }
+
+                    "plugin_message" => {
+                        gen.plugin_message = Some(field.expr);
+                    }
+
                     _ => {
                         match_parse!(
                             name,
@@ -154,7 +233,9 @@ impl syn::parse::Parse for ArcDpsGen {
                             unofficial_extras_init,
                             unofficial_extras_squad_update,
                             unofficial_extras_chat_message,
-                            unofficial_extras_chat_message2
+                            unofficial_extras_chat_message2,
+                            unofficial_extras_language_changed,
+                            unofficial_extras_keybind_changed
                         )
                     }
                 };
@@ -167,6 +248,42 @@ impl syn::parse::Parse for ArcDpsGen {
             return Err(Error::new(input.span(), "sig field is required"));
         }
 
+        if let Some(channel) = &gen.combat_channel {
+            if gen.combat.is_some() || gen.raw_combat.is_some() {
+                return Err(Error::new_spanned(
+                    channel,
+                    "combat_channel is exclusive with combat and raw_combat",
+                ));
+            }
+        }
+
+        if let Some(channel) = &gen.combat_local_channel {
+            if gen.combat_local.is_some() || gen.raw_combat_local.is_some() {
+                return Err(Error::new_spanned(
+                    channel,
+                    "combat_local_channel is exclusive with combat_local and raw_combat_local",
+                ));
+            }
+        }
+
+        if let Some(message) = &gen.plugin_message {
+            if gen.raw_combat.is_some() || gen.raw_combat_local.is_some() {
+                return Err(Error::new_spanned(
+                    message,
+                    "plugin_message is exclusive with raw_combat and raw_combat_local",
+                ));
+            }
+        }
+
+        if let Some(plugin) = &gen.plugin {
+            if gen.has_callbacks() {
+                return Err(Error::new_spanned(
+                    plugin,
+                    "plugin is exclusive with init, release and all callback fields",
+                ));
+            }
+        }
+
         Ok(gen)
     }
 }
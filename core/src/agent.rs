@@ -0,0 +1,251 @@
+use std::fmt::{Display, Formatter};
+
+use crate::{Agent, AgentOwned};
+
+/// Classification of an agent by its `prof` and `elite` fields.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum AgentKind {
+    /// A player character.
+    Player {
+        profession: Profession,
+        /// [`None`] for the core specialization.
+        specialization: Option<Specialization>,
+    },
+    /// A non-player character, e.g. a boss or a minion.
+    Npc { species_id: u16 },
+    /// A gadget, e.g. an attackable object.
+    Gadget { gadget_id: u16 },
+}
+
+impl AgentKind {
+    /// Classifies an agent. `elite == 0xffffffff` marks a non-player, in which
+    /// case the upper 16 bits of `prof` are `0xffff` for gadgets and the lower
+    /// 16 bits are the species or gadget id.
+    pub fn from_raw(prof: u32, elite: u32) -> Self {
+        if elite != u32::MAX {
+            AgentKind::Player {
+                profession: Profession::from(prof),
+                specialization: (elite != 0).then(|| Specialization::from(elite)),
+            }
+        } else if prof >> 16 == 0xffff {
+            AgentKind::Gadget {
+                gadget_id: prof as u16,
+            }
+        } else {
+            AgentKind::Npc {
+                species_id: prof as u16,
+            }
+        }
+    }
+
+    pub fn is_player(&self) -> bool {
+        matches!(self, AgentKind::Player { .. })
+    }
+
+    pub fn is_npc(&self) -> bool {
+        matches!(self, AgentKind::Npc { .. })
+    }
+
+    pub fn is_gadget(&self) -> bool {
+        matches!(self, AgentKind::Gadget { .. })
+    }
+}
+
+impl Agent<'_> {
+    /// Classifies the agent, see [AgentKind].
+    /// ### Remarks
+    /// Agents of the agent added and removed notifications do not follow this
+    /// layout.
+    pub fn kind(&self) -> AgentKind {
+        AgentKind::from_raw(self.prof, self.elite)
+    }
+}
+
+impl AgentOwned {
+    /// Classifies the agent, see [`Agent::kind`].
+    pub fn kind(&self) -> AgentKind {
+        AgentKind::from_raw(self.prof, self.elite)
+    }
+}
+
+macro_rules! id_enum {
+    (
+        $(#[$attr:meta])*
+        pub enum $name:ident {
+            $($variant:ident = $value:literal,)*
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+        #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+        pub enum $name {
+            $($variant,)*
+            /// An id that is not known to this crate.
+            Unknown(u32),
+        }
+
+        impl $name {
+            pub fn id(&self) -> u32 {
+                match self {
+                    $(Self::$variant => $value,)*
+                    Self::Unknown(id) => *id,
+                }
+            }
+
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => stringify!($variant),)*
+                    Self::Unknown(_) => "Unknown",
+                }
+            }
+        }
+
+        impl From<u32> for $name {
+            fn from(id: u32) -> Self {
+                match id {
+                    $($value => Self::$variant,)*
+                    _ => Self::Unknown(id),
+                }
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.name())
+            }
+        }
+    };
+}
+
+id_enum! {
+    /// The profession of a player.
+    pub enum Profession {
+        Guardian     = 1,
+        Warrior      = 2,
+        Engineer     = 3,
+        Ranger       = 4,
+        Thief        = 5,
+        Elementalist = 6,
+        Mesmer       = 7,
+        Necromancer  = 8,
+        Revenant     = 9,
+    }
+}
+
+id_enum! {
+    /// The elite specialization of a player. The ids are the ids of the game
+    /// api.
+    pub enum Specialization {
+        Druid        = 5,
+        Daredevil    = 7,
+        Berserker    = 18,
+        Dragonhunter = 27,
+        Reaper       = 34,
+        Chronomancer = 40,
+        Scrapper     = 43,
+        Tempest      = 48,
+        Herald       = 52,
+        Soulbeast    = 55,
+        Weaver       = 56,
+        Holosmith    = 57,
+        Deadeye      = 58,
+        Mirage       = 59,
+        Scourge      = 60,
+        Spellbreaker = 61,
+        Firebrand    = 62,
+        Renegade     = 63,
+        Harbinger    = 64,
+        Willbender   = 65,
+        Virtuoso     = 66,
+        Catalyst     = 67,
+        Bladesworn   = 68,
+        Vindicator   = 69,
+        Mechanist    = 70,
+        Specter      = 71,
+        Untamed      = 72,
+    }
+}
+
+impl Specialization {
+    /// The profession the specialization belongs to. Unknown for unknown
+    /// specializations.
+    pub fn profession(&self) -> Profession {
+        match self {
+            Specialization::Dragonhunter
+            | Specialization::Firebrand
+            | Specialization::Willbender => Profession::Guardian,
+            Specialization::Berserker
+            | Specialization::Spellbreaker
+            | Specialization::Bladesworn => Profession::Warrior,
+            Specialization::Scrapper | Specialization::Holosmith | Specialization::Mechanist => {
+                Profession::Engineer
+            }
+            Specialization::Druid | Specialization::Soulbeast | Specialization::Untamed => {
+                Profession::Ranger
+            }
+            Specialization::Daredevil | Specialization::Deadeye | Specialization::Specter => {
+                Profession::Thief
+            }
+            Specialization::Tempest | Specialization::Weaver | Specialization::Catalyst => {
+                Profession::Elementalist
+            }
+            Specialization::Chronomancer | Specialization::Mirage | Specialization::Virtuoso => {
+                Profession::Mesmer
+            }
+            Specialization::Reaper | Specialization::Scourge | Specialization::Harbinger => {
+                Profession::Necromancer
+            }
+            Specialization::Herald | Specialization::Renegade | Specialization::Vindicator => {
+                Profession::Revenant
+            }
+            Specialization::Unknown(_) => Profession::Unknown(0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_round_trip() {
+        assert_eq!(Profession::from(7), Profession::Mesmer);
+        assert_eq!(Profession::Mesmer.id(), 7);
+        assert_eq!(Specialization::from(62), Specialization::Firebrand);
+        assert_eq!(Specialization::Firebrand.profession(), Profession::Guardian);
+    }
+
+    #[test]
+    fn unknown_ids_are_kept() {
+        assert_eq!(Profession::from(10), Profession::Unknown(10));
+        assert_eq!(Specialization::from(73).id(), 73);
+        assert_eq!(Specialization::from(73).to_string(), "Unknown");
+    }
+
+    #[test]
+    fn classifies_agents() {
+        assert_eq!(
+            AgentKind::from_raw(2, 0),
+            AgentKind::Player {
+                profession: Profession::Warrior,
+                specialization: None,
+            }
+        );
+        assert_eq!(
+            AgentKind::from_raw(4, 99),
+            AgentKind::Player {
+                profession: Profession::Ranger,
+                specialization: Some(Specialization::Unknown(99)),
+            }
+        );
+        assert_eq!(
+            AgentKind::from_raw(15438, u32::MAX),
+            AgentKind::Npc { species_id: 15438 }
+        );
+        assert_eq!(
+            AgentKind::from_raw(0xffff_0010, u32::MAX),
+            AgentKind::Gadget { gadget_id: 0x10 }
+        );
+    }
+}
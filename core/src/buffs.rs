@@ -0,0 +1,430 @@
+use std::collections::HashMap;
+
+use crate::{helpers::CombatEventArgs, BuffRemove, CombatEvent, CombatEventKind, StateChange};
+
+/// How the stacks of a buff combine.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum Stacking {
+    /// Every stack adds to the effect, e.g. might or bleeding.
+    Intensity,
+    /// Only one stack is active at a time, the others are queued, e.g. fury
+    /// or quickness.
+    Duration,
+}
+
+/// Uptime of a buff on an agent. See [BuffTracker].
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct BuffUptime {
+    /// Share of the time the buff was active, from 0 to 1.
+    pub uptime: f64,
+    /// Average number of stacks. Capped to one for [`Stacking::Duration`].
+    pub average_stacks: f64,
+}
+
+/// A section of a fight. See [`BuffTracker::begin_phase`].
+#[derive(Debug, Clone, Default)]
+pub struct Phase {
+    pub name: String,
+    pub start: u64,
+    /// [`None`] for the current phase.
+    pub end: Option<u64>,
+    totals: Totals,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Totals {
+    /// Integral of the stack count in milliseconds, per agent and buff.
+    stack_ms: HashMap<(usize, u32), u64>,
+    /// Time in milliseconds the buff had at least one stack, per agent and
+    /// buff.
+    uptime_ms: HashMap<(usize, u32), u64>,
+    /// Applied duration in milliseconds without overstack, per source and
+    /// buff.
+    generated_ms: HashMap<(usize, u32), u64>,
+}
+
+/// The stacks of a buff on an agent.
+#[derive(Debug, Clone, Default)]
+struct BuffState {
+    /// Stack ids in order of application.
+    stacks: Vec<u32>,
+    /// Time up to which the stacks are accounted for.
+    since: u64,
+}
+
+/// Tracks the uptime and generation of boons and conditions from buff apply
+/// and buff remove events.
+///
+/// ```
+/// use arcdps::{helpers::CombatEventArgs, BuffTracker};
+///
+/// const MIGHT: u32 = 740;
+///
+/// fn combat(buffs: &mut BuffTracker, args: &CombatEventArgs, me: usize) {
+///     buffs.process(args);
+///     let might = buffs.uptime(me, MIGHT);
+///     println!("{:.1} might", might.average_stacks);
+/// }
+/// ```
+///
+/// ### Remarks
+/// Buffs stack by intensity unless a [`StateChange::BuffInfo`] event or
+/// [`BuffTracker::set_stacking`] says otherwise. Stacks end with the buff
+/// remove events arcdps sends, not when their duration runs out.
+#[derive(Debug, Clone, Default)]
+pub struct BuffTracker {
+    states: HashMap<(usize, u32), BuffState>,
+    stacking: HashMap<u32, Stacking>,
+    fight: Phase,
+    phases: Vec<Phase>,
+    /// Time of the latest event, [`None`] before the first event.
+    now: Option<u64>,
+}
+
+impl BuffTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how the stacks of a buff combine.
+    pub fn set_stacking(&mut self, buff_id: u32, stacking: Stacking) {
+        self.stacking.insert(buff_id, stacking);
+    }
+
+    /// Adds the event of a combat callback.
+    pub fn process(&mut self, args: &CombatEventArgs) {
+        if let Some(ev) = args.ev {
+            self.add_event(ev);
+        }
+    }
+
+    /// Adds an event. Events must be added in order.
+    pub fn add_event(&mut self, ev: &CombatEvent) {
+        if self.now.is_none() {
+            self.fight.start = ev.time;
+        }
+        self.now = Some(self.now.map_or(ev.time, |now| now.max(ev.time)));
+
+        match ev.kind() {
+            CombatEventKind::BuffApply(apply) => {
+                self.apply(
+                    ev,
+                    apply.stack_id(),
+                    apply.duration(),
+                    apply.overstack(),
+                    apply.is_extension(),
+                );
+            }
+            CombatEventKind::BuffRemove(remove) => {
+                let key = (ev.src_agent, remove.buff_id());
+                let stack_id = remove.stack_id();
+                self.update(key, ev.time, |stacks| match remove.buff_remove() {
+                    Ok(BuffRemove::All) => stacks.clear(),
+                    Ok(BuffRemove::Single | BuffRemove::Manual) => {
+                        match stacks.iter().position(|&id| id == stack_id) {
+                            Some(i) => {
+                                stacks.remove(i);
+                            }
+                            None if !stacks.is_empty() => {
+                                stacks.remove(0);
+                            }
+                            None => {}
+                        }
+                    }
+                    _ => {}
+                });
+            }
+            CombatEventKind::StateChange(state) => match state.state_change() {
+                StateChange::BuffInitial => {
+                    self.apply(ev, ev.pad_u32(), ev.value, ev.overstack_value, false);
+                }
+                StateChange::BuffInfo => {
+                    if let Some(info) = state.buff_info() {
+                        let stacking = match info.stacking_type {
+                            1 | 3 | 5 => Stacking::Duration,
+                            _ => Stacking::Intensity,
+                        };
+                        self.stacking.entry(info.buff_id).or_insert(stacking);
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    fn apply(
+        &mut self,
+        ev: &CombatEvent,
+        stack_id: u32,
+        duration: i32,
+        overstack: u32,
+        extension: bool,
+    ) {
+        let key = (ev.dst_agent, ev.skill_id);
+        self.update(key, ev.time, |stacks| {
+            // an extension only changes the duration of its stack, unless the
+            // stack was applied before the tracking started
+            if !extension || !stacks.contains(&stack_id) {
+                stacks.push(stack_id);
+            }
+        });
+
+        let generated = (duration.max(0) as u64).saturating_sub(overstack as u64);
+        let gen_key = (ev.src_agent, ev.skill_id);
+        for phase in self.phases.iter_mut().chain([&mut self.fight]) {
+            if phase.end.is_none() {
+                *phase.totals.generated_ms.entry(gen_key).or_default() += generated;
+            }
+        }
+    }
+
+    /// Accounts for the time since the last change of the buff and changes
+    /// the stacks.
+    fn update(&mut self, key: (usize, u32), time: u64, f: impl FnOnce(&mut Vec<u32>)) {
+        let stacking = self.stacking(key.1);
+        let state = self.states.entry(key).or_insert_with(|| BuffState {
+            stacks: Vec::new(),
+            since: time,
+        });
+        let open_phases = self
+            .phases
+            .last_mut()
+            .into_iter()
+            .chain([&mut self.fight])
+            .filter(|phase| phase.end.is_none());
+        for phase in open_phases {
+            accumulate(&mut phase.totals, key, state, stacking, time);
+        }
+        state.since = time;
+        f(&mut state.stacks);
+    }
+
+    fn stacking(&self, buff_id: u32) -> Stacking {
+        self.stacking
+            .get(&buff_id)
+            .copied()
+            .unwrap_or(Stacking::Intensity)
+    }
+
+    /// Ends the current phase and starts a new one at `time`.
+    pub fn begin_phase(&mut self, name: impl Into<String>, time: u64) {
+        self.end_phase(time);
+        self.phases.push(Phase {
+            name: name.into(),
+            start: time,
+            end: None,
+            totals: Totals::default(),
+        });
+    }
+
+    /// Ends the current phase at `time`.
+    pub fn end_phase(&mut self, time: u64) {
+        let stacking = &self.stacking;
+        let mut phase = self.phases.last_mut().filter(|phase| phase.end.is_none());
+        for (&key, state) in &mut self.states {
+            let stacking = stacking.get(&key.1).copied().unwrap_or(Stacking::Intensity);
+            if let Some(phase) = &mut phase {
+                accumulate(&mut phase.totals, key, state, stacking, time);
+            }
+            accumulate(&mut self.fight.totals, key, state, stacking, time);
+            state.since = state.since.max(time);
+        }
+        if let Some(phase) = phase {
+            phase.end = Some(time);
+        }
+    }
+
+    /// Returns all phases that were started with
+    /// [`BuffTracker::begin_phase`].
+    pub fn phases(&self) -> &[Phase] {
+        &self.phases
+    }
+
+    /// Uptime of `buff_id` on `agent` over the whole fight.
+    pub fn uptime(&self, agent: usize, buff_id: u32) -> BuffUptime {
+        self.phase_uptime_of(&self.fight, agent, buff_id)
+    }
+
+    /// Uptime of `buff_id` on `agent` during a phase.
+    pub fn phase_uptime(&self, phase: usize, agent: usize, buff_id: u32) -> Option<BuffUptime> {
+        let phase = self.phases.get(phase)?;
+        Some(self.phase_uptime_of(phase, agent, buff_id))
+    }
+
+    fn phase_uptime_of(&self, phase: &Phase, agent: usize, buff_id: u32) -> BuffUptime {
+        let key = (agent, buff_id);
+        let end = phase.end.or(self.now).unwrap_or(phase.start);
+        let duration = end.saturating_sub(phase.start);
+        if duration == 0 {
+            return BuffUptime::default();
+        }
+
+        let mut totals = Totals::default();
+        totals
+            .stack_ms
+            .extend(phase.totals.stack_ms.get_key_value(&key));
+        totals
+            .uptime_ms
+            .extend(phase.totals.uptime_ms.get_key_value(&key));
+        if let (None, Some(state)) = (phase.end, self.states.get(&key)) {
+            accumulate(&mut totals, key, state, self.stacking(buff_id), end);
+        }
+
+        BuffUptime {
+            uptime: totals.uptime_ms.get(&key).copied().unwrap_or_default() as f64
+                / duration as f64,
+            average_stacks: totals.stack_ms.get(&key).copied().unwrap_or_default() as f64
+                / duration as f64,
+        }
+    }
+
+    /// Duration of `buff_id` that `src` applied over the whole fight, relative
+    /// to the duration of the fight. Summed over all targets, so 1.0 means
+    /// that `src` kept the buff up on one target.
+    pub fn generation(&self, src: usize, buff_id: u32) -> f64 {
+        self.generation_of(&self.fight, src, buff_id)
+    }
+
+    /// Like [`BuffTracker::generation`], during a phase.
+    pub fn phase_generation(&self, phase: usize, src: usize, buff_id: u32) -> Option<f64> {
+        let phase = self.phases.get(phase)?;
+        Some(self.generation_of(phase, src, buff_id))
+    }
+
+    fn generation_of(&self, phase: &Phase, src: usize, buff_id: u32) -> f64 {
+        let duration = phase
+            .end
+            .or(self.now)
+            .unwrap_or(phase.start)
+            .saturating_sub(phase.start);
+        if duration == 0 {
+            return 0.0;
+        }
+        let generated = phase.totals.generated_ms.get(&(src, buff_id));
+        generated.copied().unwrap_or_default() as f64 / duration as f64
+    }
+
+    /// Removes all buffs and phases, e.g. when a new fight starts.
+    pub fn clear(&mut self) {
+        self.states.clear();
+        self.phases.clear();
+        self.fight = Phase::default();
+        self.now = None;
+    }
+}
+
+fn accumulate(
+    totals: &mut Totals,
+    key: (usize, u32),
+    state: &BuffState,
+    stacking: Stacking,
+    time: u64,
+) {
+    let elapsed = time.saturating_sub(state.since);
+    if elapsed == 0 || state.stacks.is_empty() {
+        return;
+    }
+    let stacks = match stacking {
+        Stacking::Intensity => state.stacks.len() as u64,
+        Stacking::Duration => 1,
+    };
+    *totals.stack_ms.entry(key).or_default() += stacks * elapsed;
+    *totals.uptime_ms.entry(key).or_default() += elapsed;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::test_events::{at, buff_apply, buff_remove, events};
+
+    const AGENT: usize = 0x10;
+    const SRC: usize = 0x20;
+    const MIGHT: u32 = 740;
+    const QUICKNESS: u32 = 1187;
+
+    #[test]
+    fn intensity_stacks() {
+        let mut buffs = BuffTracker::new();
+        events(&[
+            buff_apply(0, SRC, AGENT, MIGHT, 1, 1000),
+            buff_apply(0, SRC, AGENT, MIGHT, 2, 1000),
+            buff_remove(1000, AGENT, MIGHT, BuffRemove::Single, 1),
+            buff_remove(2000, AGENT, MIGHT, BuffRemove::All, 0),
+            at(4000),
+        ])
+        .for_each(|ev| buffs.add_event(&ev));
+        let might = buffs.uptime(AGENT, MIGHT);
+        assert_eq!(might.uptime, 0.5);
+        assert_eq!(might.average_stacks, 0.75);
+        assert_eq!(buffs.generation(SRC, MIGHT), 0.5);
+    }
+
+    #[test]
+    fn duration_stacks() {
+        let mut buffs = BuffTracker::new();
+        buffs.set_stacking(QUICKNESS, Stacking::Duration);
+        events(&[
+            buff_apply(0, SRC, AGENT, QUICKNESS, 1, 1000),
+            buff_apply(0, SRC, AGENT, QUICKNESS, 2, 3000),
+            buff_remove(1000, AGENT, QUICKNESS, BuffRemove::Single, 1),
+            buff_remove(3000, AGENT, QUICKNESS, BuffRemove::Manual, 2),
+            at(4000),
+        ])
+        .for_each(|ev| buffs.add_event(&ev));
+        let quickness = buffs.uptime(AGENT, QUICKNESS);
+        assert_eq!(quickness.uptime, 0.75);
+        assert_eq!(quickness.average_stacks, 0.75);
+    }
+
+    #[test]
+    fn extensions_keep_the_stack() {
+        let mut buffs = BuffTracker::new();
+        buffs.set_stacking(QUICKNESS, Stacking::Duration);
+        events(&[
+            buff_apply(0, SRC, AGENT, MIGHT, 1, 1000),
+            buff_apply(500, SRC, AGENT, MIGHT, 1, 500).is_off_cycle(true),
+            buff_apply(0, SRC, AGENT, QUICKNESS, 2, 1000),
+            buff_apply(500, SRC, AGENT, QUICKNESS, 2, 500).is_off_cycle(true),
+            buff_remove(1000, AGENT, QUICKNESS, BuffRemove::Single, 2),
+            // an extension of a stack from before the tracking started
+            buff_apply(1000, SRC, AGENT, MIGHT, 3, 500).is_off_cycle(true),
+            at(2000),
+        ])
+        .for_each(|ev| buffs.add_event(&ev));
+        assert_eq!(buffs.uptime(AGENT, MIGHT).average_stacks, 1.5);
+        assert_eq!(buffs.uptime(AGENT, QUICKNESS).uptime, 0.5);
+    }
+
+    #[test]
+    fn fight_starts_at_time_zero() {
+        let mut buffs = BuffTracker::new();
+        events(&[buff_apply(0, SRC, AGENT, MIGHT, 1, 1000), at(0), at(1000)])
+            .for_each(|ev| buffs.add_event(&ev));
+        assert_eq!(buffs.uptime(AGENT, MIGHT).uptime, 1.0);
+    }
+
+    #[test]
+    fn splits_phases() {
+        let mut buffs = BuffTracker::new();
+        buffs.begin_phase("first", 0);
+        events(&[buff_apply(0, SRC, AGENT, MIGHT, 1, 1000)]).for_each(|ev| buffs.add_event(&ev));
+        buffs.begin_phase("second", 1000);
+        events(&[
+            buff_remove(1500, AGENT, MIGHT, BuffRemove::All, 0),
+            at(2000),
+        ])
+        .for_each(|ev| buffs.add_event(&ev));
+        buffs.end_phase(2000);
+
+        assert_eq!(buffs.phases().len(), 2);
+        assert_eq!(buffs.phases()[1].end, Some(2000));
+        assert_eq!(buffs.phase_uptime(0, AGENT, MIGHT).unwrap().uptime, 1.0);
+        assert_eq!(buffs.phase_uptime(1, AGENT, MIGHT).unwrap().uptime, 0.5);
+        assert_eq!(buffs.uptime(AGENT, MIGHT).uptime, 0.75);
+        assert_eq!(buffs.phase_generation(0, SRC, MIGHT), Some(1.0));
+        assert_eq!(buffs.phase_generation(1, SRC, MIGHT), Some(0.0));
+    }
+}
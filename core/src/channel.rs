@@ -0,0 +1,470 @@
+use std::{
+    cell::UnsafeCell,
+    fmt::{Debug, Display, Formatter},
+    mem::MaybeUninit,
+    ops::Deref,
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+};
+
+use crate::{helpers::CombatEventArgs, Agent, CombatEvent};
+
+/// A name stored inline, without allocating. Names longer than the buffer
+/// are cut off at a character boundary.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct InlineName {
+    len: u8,
+    buf: [u8; Self::CAPACITY],
+}
+
+impl InlineName {
+    /// The maximum length of a name in bytes.
+    pub const CAPACITY: usize = 63;
+
+    pub fn new(name: &str) -> Self {
+        let mut len = name.len().min(Self::CAPACITY);
+        while !name.is_char_boundary(len) {
+            len -= 1;
+        }
+        let mut buf = [0; Self::CAPACITY];
+        buf[..len].copy_from_slice(&name.as_bytes()[..len]);
+        Self {
+            len: len as u8,
+            buf,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // SAFETY: the buffer is copied from a str and cut at a char boundary
+        unsafe { std::str::from_utf8_unchecked(&self.buf[..self.len as usize]) }
+    }
+}
+
+impl Deref for InlineName {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl Debug for InlineName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl Display for InlineName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self.as_str(), f)
+    }
+}
+
+/// An [`Agent`] with an [`InlineName`].
+/// For more info see [`Agent`].
+#[derive(Debug, Copy, Clone)]
+pub struct AgentInline {
+    pub name: Option<InlineName>,
+    pub id: usize,
+    pub prof: u32,
+    pub elite: u32,
+    pub self_: u32,
+    pub team: u16,
+}
+
+impl AgentInline {
+    pub fn as_agent(&self) -> Agent<'_> {
+        Agent {
+            name: self.name.as_ref().map(InlineName::as_str),
+            id: self.id,
+            prof: self.prof,
+            elite: self.elite,
+            self_: self.self_,
+            team: self.team,
+        }
+    }
+}
+
+impl From<Agent<'_>> for AgentInline {
+    fn from(ag: Agent<'_>) -> Self {
+        AgentInline {
+            name: ag.name.map(InlineName::new),
+            id: ag.id,
+            prof: ag.prof,
+            elite: ag.elite,
+            self_: ag.self_,
+            team: ag.team,
+        }
+    }
+}
+
+/// The arguments of a combat callback, copied so they can be sent to another
+/// thread. See [CombatChannel].
+#[derive(Debug, Copy, Clone)]
+pub struct CombatMessage {
+    pub ev: Option<CombatEvent>,
+    pub src: Option<AgentInline>,
+    pub dst: Option<AgentInline>,
+    /// Skill names stay valid for the lifetime of the process, so they are
+    /// not copied.
+    pub skill_name: Option<&'static str>,
+    pub id: u64,
+    pub revision: u64,
+}
+
+impl CombatMessage {
+    /// Copies the arguments of a [CombatCallback](crate::CombatCallback).
+    pub fn new(
+        ev: Option<&CombatEvent>,
+        src: Option<Agent>,
+        dst: Option<Agent>,
+        skill_name: Option<&'static str>,
+        id: u64,
+        revision: u64,
+    ) -> Self {
+        Self {
+            ev: ev.copied(),
+            src: src.map(Into::into),
+            dst: dst.map(Into::into),
+            skill_name,
+            id,
+            revision,
+        }
+    }
+
+    /// Borrows the message as the arguments of a combat callback, e.g. to pass
+    /// it to [`AgentRegistry::process`](crate::AgentRegistry::process).
+    pub fn args(&self) -> CombatEventArgs<'_> {
+        CombatEventArgs {
+            ev: self.ev.as_ref(),
+            src: self.src.as_ref().map(AgentInline::as_agent),
+            dst: self.dst.as_ref().map(AgentInline::as_agent),
+            skill_name: self.skill_name,
+        }
+    }
+}
+
+struct Slot {
+    /// Sequence number of the slot, minus the index of the slot. That way all
+    /// slots start at 0 and the array can be created in a const context.
+    seq: AtomicUsize,
+    value: UnsafeCell<MaybeUninit<CombatMessage>>,
+}
+
+impl Slot {
+    const fn new() -> Self {
+        Self {
+            seq: AtomicUsize::new(0),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+}
+
+/// A bounded lock-free queue that passes combat events from the callbacks of
+/// arcdps to the threads of the plugin, without allocating.
+///
+/// Pass a static channel as `combat_channel` or `combat_local_channel` to
+/// [arcdps_export!](crate::arcdps_export) instead of a callback. The generated
+/// callback copies every event into a [CombatMessage] and pushes it onto the
+/// channel. The capacity `N` must be a power of two.
+///
+/// ```no_run
+/// use std::{thread, time::Duration};
+///
+/// use arcdps::{AgentRegistry, CombatChannel};
+///
+/// static COMBAT: CombatChannel = CombatChannel::new();
+///
+/// arcdps::arcdps_export! {
+///     name: "example addon",
+///     sig: 123,
+///     combat_channel: COMBAT,
+/// }
+///
+/// fn worker() {
+///     let mut registry = AgentRegistry::new();
+///     loop {
+///         if COMBAT.take_dropped() > 0 {
+///             // notifications may have been lost
+///             registry.clear();
+///         }
+///         for message in COMBAT.drain() {
+///             registry.process(&message.args());
+///         }
+///         thread::sleep(Duration::from_millis(10));
+///     }
+/// }
+/// # fn main() {}
+/// ```
+///
+/// ### Remarks
+/// The channel never blocks arcdps. If it is full, new events are dropped and
+/// counted, see [`CombatChannel::dropped`]. This includes the agent
+/// notifications, so state built from them should be reset after a drop.
+/// Consumers poll the channel, it does not wake them up.
+/// Events are normalized to revision 1 before they are pushed, see
+/// [`CombatEvent::normalized`]. [`CombatMessage::revision`] is the revision
+/// arcdps passed.
+pub struct CombatChannel<const N: usize = 1024> {
+    slots: [Slot; N],
+    /// Position of the next pop.
+    head: AtomicUsize,
+    /// Position of the next push.
+    tail: AtomicUsize,
+    dropped: AtomicU64,
+}
+
+// SAFETY: a slot is only accessed by the thread that claimed its position
+unsafe impl<const N: usize> Sync for CombatChannel<N> {}
+
+impl<const N: usize> Default for CombatChannel<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> CombatChannel<N> {
+    pub const fn new() -> Self {
+        assert!(N.is_power_of_two(), "the capacity must be a power of two");
+        Self {
+            slots: [const { Slot::new() }; N],
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    fn slot(&self, pos: usize) -> (&Slot, usize) {
+        let index = pos & (N - 1);
+        let slot = &self.slots[index];
+        (slot, slot.seq.load(Ordering::Acquire).wrapping_add(index))
+    }
+
+    fn set_seq(&self, pos: usize, seq: usize) {
+        let index = pos & (N - 1);
+        self.slots[index]
+            .seq
+            .store(seq.wrapping_sub(index), Ordering::Release);
+    }
+
+    /// Pushes a message. Returns `false` and counts the message as dropped if
+    /// the channel is full.
+    pub fn push(&self, message: CombatMessage) -> bool {
+        let mut pos = self.tail.load(Ordering::Relaxed);
+        loop {
+            let (slot, seq) = self.slot(pos);
+            let diff = seq.wrapping_sub(pos) as isize;
+            if diff == 0 {
+                match self.tail.compare_exchange_weak(
+                    pos,
+                    pos.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // SAFETY: the position was claimed by this thread
+                        unsafe { (*slot.value.get()).write(message) };
+                        self.set_seq(pos, pos.wrapping_add(1));
+                        return true;
+                    }
+                    Err(current) => pos = current,
+                }
+            } else if diff < 0 {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                return false;
+            } else {
+                pos = self.tail.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Pops the oldest message, if there is one.
+    pub fn pop(&self) -> Option<CombatMessage> {
+        let mut pos = self.head.load(Ordering::Relaxed);
+        loop {
+            let (slot, seq) = self.slot(pos);
+            let diff = seq.wrapping_sub(pos.wrapping_add(1)) as isize;
+            if diff == 0 {
+                match self.head.compare_exchange_weak(
+                    pos,
+                    pos.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // SAFETY: the position was claimed by this thread and
+                        // the producer finished writing before it set the seq
+                        let message = unsafe { (*slot.value.get()).assume_init() };
+                        self.set_seq(pos, pos.wrapping_add(N));
+                        return Some(message);
+                    }
+                    Err(current) => pos = current,
+                }
+            } else if diff < 0 {
+                return None;
+            } else {
+                pos = self.head.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Pops messages until the channel is empty.
+    pub fn drain(&self) -> impl Iterator<Item = CombatMessage> + '_ {
+        std::iter::from_fn(|| self.pop())
+    }
+
+    /// Number of messages in the channel. Only a snapshot while other threads
+    /// push or pop.
+    pub fn len(&self) -> usize {
+        // head first, so it is never ahead of tail
+        let head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::Relaxed);
+        tail.wrapping_sub(head).min(N)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of messages that were dropped because the channel was full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of dropped messages and resets it.
+    pub fn take_dropped(&self) -> u64 {
+        self.dropped.swap(0, Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, thread};
+
+    use super::*;
+
+    fn message(id: u64) -> CombatMessage {
+        CombatMessage::new(None, None, None, None, id, 1)
+    }
+
+    fn ids<const N: usize>(channel: &CombatChannel<N>) -> Vec<u64> {
+        channel.drain().map(|message| message.id).collect()
+    }
+
+    #[test]
+    fn fifo() {
+        let channel = CombatChannel::<8>::new();
+        assert!(channel.is_empty());
+        for id in 0..5 {
+            assert!(channel.push(message(id)));
+        }
+        assert_eq!(channel.len(), 5);
+        assert_eq!(channel.pop().map(|message| message.id), Some(0));
+        assert_eq!(ids(&channel), [1, 2, 3, 4]);
+        assert_eq!(channel.pop().map(|message| message.id), None);
+    }
+
+    #[test]
+    fn drops_when_full() {
+        let channel = CombatChannel::<4>::new();
+        for id in 0..4 {
+            assert!(channel.push(message(id)));
+        }
+        assert!(!channel.push(message(4)));
+        assert!(!channel.push(message(5)));
+        assert_eq!(channel.len(), 4);
+        assert_eq!(channel.dropped(), 2);
+        assert_eq!(channel.take_dropped(), 2);
+        assert_eq!(channel.dropped(), 0);
+        assert_eq!(ids(&channel), [0, 1, 2, 3]);
+        assert!(channel.push(message(6)));
+    }
+
+    #[test]
+    fn wraps_around() {
+        let channel = CombatChannel::<4>::new();
+        let mut next = 0;
+        for round in 0..10 {
+            let count = round % 4 + 1;
+            for id in next..next + count {
+                assert!(channel.push(message(id)));
+            }
+            assert_eq!(ids(&channel), (next..next + count).collect::<Vec<_>>());
+            next += count;
+        }
+        assert!(next > 4 * 4);
+        assert_eq!(channel.dropped(), 0);
+    }
+
+    #[test]
+    fn cuts_names_at_char_boundary() {
+        let ascii = "a".repeat(InlineName::CAPACITY);
+        assert_eq!(InlineName::new(&ascii).as_str(), ascii);
+        assert_eq!(InlineName::new(&format!("{ascii}b")).as_str(), ascii);
+
+        // the 2 byte 'é' would end at byte 64
+        let name = format!("{}é", "a".repeat(InlineName::CAPACITY - 1));
+        assert_eq!(
+            InlineName::new(&name).as_str(),
+            "a".repeat(InlineName::CAPACITY - 1)
+        );
+        assert_eq!(InlineName::new("Nikaré").to_string(), "Nikaré");
+    }
+
+    #[test]
+    fn concurrent() {
+        const PRODUCERS: u64 = 4;
+        const MESSAGES: u64 = 10_000;
+        const TOTAL: u64 = PRODUCERS * MESSAGES;
+
+        let channel = CombatChannel::<64>::new();
+        let consumed = AtomicU64::new(0);
+        let received: Vec<u64> = thread::scope(|scope| {
+            for producer in 0..PRODUCERS {
+                let channel = &channel;
+                scope.spawn(move || {
+                    for i in 0..MESSAGES {
+                        while !channel.push(message(producer << 32 | i)) {
+                            thread::yield_now();
+                        }
+                    }
+                });
+            }
+            let consumers: Vec<_> = (0..2)
+                .map(|_| {
+                    let (channel, consumed) = (&channel, &consumed);
+                    scope.spawn(move || {
+                        let mut received = Vec::new();
+                        let mut last = [None; PRODUCERS as usize];
+                        while consumed.load(Ordering::Relaxed) < TOTAL {
+                            let Some(message) = channel.pop() else {
+                                thread::yield_now();
+                                continue;
+                            };
+                            consumed.fetch_add(1, Ordering::Relaxed);
+                            // a consumer sees the messages of a producer in
+                            // order
+                            let producer = (message.id >> 32) as usize;
+                            assert!(last[producer] < Some(message.id));
+                            last[producer] = Some(message.id);
+                            received.push(message.id);
+                        }
+                        received
+                    })
+                })
+                .collect();
+            consumers
+                .into_iter()
+                .flat_map(|consumer| consumer.join().unwrap())
+                .collect()
+        });
+
+        let unique: HashSet<_> = received.iter().copied().collect();
+        assert_eq!(unique.len(), received.len(), "a message was duplicated");
+        assert_eq!(received.len() as u64, TOTAL, "a message was lost");
+        assert!(channel.is_empty());
+    }
+}
@@ -0,0 +1,147 @@
+use std::ptr::null_mut;
+
+use crate::{e5, Profession};
+
+/// A color as RGBA, from 0 to 1.
+pub type Color = [f32; 4];
+
+/// Number of entries in the profession tables, including the unknown
+/// profession at index 0.
+const PROFESSION_COUNT: usize = 10;
+
+/// Number of entries in the subgroup tables.
+const SUBGROUP_COUNT: usize = 16;
+
+/// The core colors of arcdps.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct CoreColors {
+    pub transparent: Color,
+    pub white: Color,
+    pub light_white: Color,
+    pub light_grey: Color,
+    pub light_yellow: Color,
+    pub light_green: Color,
+    pub light_red: Color,
+    pub light_teal: Color,
+    pub medium_grey: Color,
+    pub dark_grey: Color,
+}
+
+/// The color palette of arcdps, copied from `e5`. The colors change when the
+/// user edits them in the arcdps options, so read them again from time to
+/// time.
+///
+/// ```no_run
+/// use arcdps::{ArcColors, Profession};
+///
+/// # #[cfg(feature = "imgui")]
+/// fn draw(ui: &arcdps::imgui::Ui) {
+///     let Some(colors) = ArcColors::read() else {
+///         return;
+///     };
+///     let _style = colors.push_style(ui);
+///     ui.text_colored(colors.profession_color(Profession::Mesmer), "Mesmer");
+/// }
+/// ```
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct ArcColors {
+    pub core: CoreColors,
+    /// Base colors of the professions, indexed by the profession id. Index 0
+    /// is the color of unknown professions.
+    pub profession_base: [Color; PROFESSION_COUNT],
+    /// Highlight colors of the professions, indexed like
+    /// [`ArcColors::profession_base`].
+    pub profession_highlight: [Color; PROFESSION_COUNT],
+    /// Base colors of the subgroups, indexed by the subgroup.
+    pub subgroup_base: [Color; SUBGROUP_COUNT],
+    /// Highlight colors of the subgroups, indexed by the subgroup.
+    pub subgroup_highlight: [Color; SUBGROUP_COUNT],
+}
+
+impl ArcColors {
+    /// Copies the color tables of arcdps. Returns [`None`] if the plugin is
+    /// not loaded or arcdps didn't provide all tables.
+    pub fn read() -> Option<Self> {
+        let mut tables: [*mut Color; 5] = [null_mut(); 5];
+        // SAFETY: e5 writes 5 pointers to color tables, imgui's ImVec4 has
+        // the layout of [f32; 4]
+        unsafe { e5((&mut tables as *mut [*mut Color; 5]).cast()) };
+        if tables.iter().any(|table| table.is_null()) {
+            return None;
+        }
+
+        // SAFETY: the tables have at least the read number of entries and
+        // arcdps keeps them alive
+        unsafe {
+            let core = std::slice::from_raw_parts(tables[0], 10);
+            Some(Self {
+                core: CoreColors {
+                    transparent: core[0],
+                    white: core[1],
+                    light_white: core[2],
+                    light_grey: core[3],
+                    light_yellow: core[4],
+                    light_green: core[5],
+                    light_red: core[6],
+                    light_teal: core[7],
+                    medium_grey: core[8],
+                    dark_grey: core[9],
+                },
+                profession_base: *tables[1].cast(),
+                profession_highlight: *tables[2].cast(),
+                subgroup_base: *tables[3].cast(),
+                subgroup_highlight: *tables[4].cast(),
+            })
+        }
+    }
+
+    /// Returns the base color of a profession. Unknown professions have the
+    /// color at index 0.
+    pub fn profession_color(&self, profession: Profession) -> Color {
+        let index = profession.id() as usize;
+        self.profession_base
+            .get(index)
+            .copied()
+            .unwrap_or(self.profession_base[0])
+    }
+
+    /// Returns the highlight color of a profession. Unknown professions have
+    /// the color at index 0.
+    pub fn profession_highlight_color(&self, profession: Profession) -> Color {
+        let index = profession.id() as usize;
+        self.profession_highlight
+            .get(index)
+            .copied()
+            .unwrap_or(self.profession_highlight[0])
+    }
+
+    /// Returns the base color of a subgroup, or [`None`] if arcdps has no
+    /// color for it.
+    pub fn subgroup_color(&self, subgroup: u8) -> Option<Color> {
+        self.subgroup_base.get(subgroup as usize).copied()
+    }
+
+    /// Returns the highlight color of a subgroup, or [`None`] if arcdps has
+    /// no color for it.
+    pub fn subgroup_highlight_color(&self, subgroup: u8) -> Option<Color> {
+        self.subgroup_highlight.get(subgroup as usize).copied()
+    }
+
+    /// Pushes the core colors onto the imgui style. The colors are popped when
+    /// the returned tokens are dropped.
+    #[cfg(feature = "imgui")]
+    pub fn push_style<'ui>(&self, ui: &'ui imgui::Ui) -> [imgui::ColorStackToken<'ui>; 6] {
+        use imgui::StyleColor;
+
+        [
+            ui.push_style_color(StyleColor::Text, self.core.light_white),
+            ui.push_style_color(StyleColor::TextDisabled, self.core.medium_grey),
+            ui.push_style_color(StyleColor::Border, self.core.dark_grey),
+            ui.push_style_color(StyleColor::Separator, self.core.dark_grey),
+            ui.push_style_color(StyleColor::PlotLines, self.core.light_teal),
+            ui.push_style_color(StyleColor::PlotHistogram, self.core.light_yellow),
+        ]
+    }
+}
@@ -0,0 +1,89 @@
+use std::{
+    fmt::{Display, Formatter},
+    path::PathBuf,
+};
+
+use crate::{runtime, Export0};
+
+/// Longest path windows supports, in UTF-16 units.
+const MAX_PATH_LEN: usize = 32767;
+
+/// An error while getting the path of the arcdps ini.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum IniPathError {
+    /// The plugin is not loaded, or already released.
+    NotLoaded,
+    /// arcdps doesn't provide the `e0` export.
+    MissingExport,
+    /// arcdps returned a null pointer or an empty path.
+    NullPath,
+    /// The path has no parent directory.
+    NoParent,
+}
+
+impl Display for IniPathError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IniPathError::NotLoaded => write!(f, "the plugin is not loaded"),
+            IniPathError::MissingExport => write!(f, "arcdps doesn't export e0"),
+            IniPathError::NullPath => write!(f, "arcdps returned no ini path"),
+            IniPathError::NoParent => write!(f, "the ini path has no parent directory"),
+        }
+    }
+}
+
+impl std::error::Error for IniPathError {}
+
+/// Returns the path of `arcdps.ini`, as returned by `e0`.
+pub fn arcdps_ini_path() -> Result<PathBuf, IniPathError> {
+    if !runtime::is_loaded() {
+        return Err(IniPathError::NotLoaded);
+    }
+    let e0 = runtime::export(0);
+    if e0.is_null() {
+        return Err(IniPathError::MissingExport);
+    }
+    // SAFETY: e0 has this signature and returns a null terminated wide string
+    // that arcdps keeps alive
+    unsafe {
+        let e0 = std::mem::transmute::<*mut std::ffi::c_void, Export0>(e0);
+        let path = e0();
+        if path.is_null() {
+            return Err(IniPathError::NullPath);
+        }
+        let len = (0..MAX_PATH_LEN)
+            .position(|i| *path.add(i) == 0)
+            .unwrap_or(MAX_PATH_LEN);
+        if len == 0 {
+            return Err(IniPathError::NullPath);
+        }
+        Ok(decode_wide(std::slice::from_raw_parts(path, len)))
+    }
+}
+
+/// Returns the directory of `arcdps.ini`, where arcdps keeps its config
+/// files. Plugins can keep their own config files next to them.
+///
+/// ```no_run
+/// let path = arcdps::arcdps_config_dir()?.join("my_plugin.json");
+/// # Ok::<(), arcdps::IniPathError>(())
+/// ```
+pub fn arcdps_config_dir() -> Result<PathBuf, IniPathError> {
+    let ini = arcdps_ini_path()?;
+    match ini.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => Ok(dir.to_path_buf()),
+        _ => Err(IniPathError::NoParent),
+    }
+}
+
+#[cfg(windows)]
+fn decode_wide(wide: &[u16]) -> PathBuf {
+    use std::os::windows::ffi::OsStringExt;
+
+    std::ffi::OsString::from_wide(wide).into()
+}
+
+#[cfg(not(windows))]
+fn decode_wide(wide: &[u16]) -> PathBuf {
+    String::from_utf16_lossy(wide).into()
+}
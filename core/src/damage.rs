@@ -0,0 +1,358 @@
+use std::{collections::HashMap, ops::AddAssign};
+
+use crate::{helpers::CombatEventArgs, CombatEvent, CombatEventKind, MasterResolver, StrikeResult};
+
+/// Damage totals of a [DamageAggregator].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct DamageStats {
+    /// Direct damage to health, without the damage absorbed by barrier.
+    pub strike_damage: i64,
+    /// Damage of buffs to health, e.g. conditions, without the damage
+    /// absorbed by barrier. Heals are not counted.
+    pub condition_damage: i64,
+    /// Damage that was absorbed by barrier.
+    pub barrier_damage: i64,
+    /// Strikes that dealt damage.
+    pub hits: u32,
+    /// Hits that were critical.
+    pub crits: u32,
+    /// Hits that were flanking.
+    pub flanking: u32,
+    /// Hits that were glancing.
+    pub glances: u32,
+}
+
+impl DamageStats {
+    /// Strike and condition damage to health.
+    pub fn damage(&self) -> i64 {
+        self.strike_damage + self.condition_damage
+    }
+
+    /// Damage to health and barrier.
+    pub fn total_damage(&self) -> i64 {
+        self.damage() + self.barrier_damage
+    }
+
+    /// Share of hits that were critical, from 0 to 1.
+    pub fn crit_rate(&self) -> f64 {
+        self.rate(self.crits)
+    }
+
+    /// Share of hits that were flanking, from 0 to 1.
+    pub fn flanking_rate(&self) -> f64 {
+        self.rate(self.flanking)
+    }
+
+    fn rate(&self, count: u32) -> f64 {
+        if self.hits == 0 {
+            0.0
+        } else {
+            count as f64 / self.hits as f64
+        }
+    }
+}
+
+impl AddAssign<&DamageStats> for DamageStats {
+    fn add_assign(&mut self, rhs: &DamageStats) {
+        self.strike_damage += rhs.strike_damage;
+        self.condition_damage += rhs.condition_damage;
+        self.barrier_damage += rhs.barrier_damage;
+        self.hits += rhs.hits;
+        self.crits += rhs.crits;
+        self.flanking += rhs.flanking;
+        self.glances += rhs.glances;
+    }
+}
+
+/// The key the [DamageAggregator] groups the damage by.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct DamageKey {
+    /// The agent that dealt the damage. The owner of minions if
+    /// [`DamageAggregator::attribute_minions`] is enabled.
+    pub src: usize,
+    /// The agent that received the damage.
+    pub dst: usize,
+    pub skill_id: u32,
+}
+
+/// Sums up the damage of a fight per source, target and skill.
+///
+/// ```
+/// use arcdps::{helpers::CombatEventArgs, DamageAggregator};
+///
+/// fn combat(damage: &mut DamageAggregator, args: &CombatEventArgs, me: usize) {
+///     damage.process(args);
+///     println!("{:.0} dps", damage.dps(me));
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DamageAggregator {
+    stats: HashMap<DamageKey, DamageStats>,
+    series: HashMap<usize, Vec<i64>>,
+    window: u64,
+    start: Option<u64>,
+    end: u64,
+    resolver: Option<MasterResolver>,
+}
+
+impl Default for DamageAggregator {
+    fn default() -> Self {
+        Self {
+            stats: HashMap::new(),
+            series: HashMap::new(),
+            window: 1000,
+            start: None,
+            end: 0,
+            resolver: Some(MasterResolver::new()),
+        }
+    }
+}
+
+impl DamageAggregator {
+    /// Creates an aggregator with a window of one second that attributes
+    /// minions to their owners.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the length of a window of the damage series in milliseconds.
+    pub fn window(mut self, window: u64) -> Self {
+        self.window = window.max(1);
+        self
+    }
+
+    /// Whether the damage of minions counts for their owner. See
+    /// [MasterResolver].
+    pub fn attribute_minions(mut self, enabled: bool) -> Self {
+        self.resolver = enabled.then(MasterResolver::new);
+        self
+    }
+
+    /// Adds the event of a combat callback.
+    pub fn process(&mut self, args: &CombatEventArgs) {
+        if let Some(ev) = args.ev {
+            self.add_event(ev);
+        }
+    }
+
+    /// Adds an event. Events must be added in order.
+    pub fn add_event(&mut self, ev: &CombatEvent) {
+        if let Some(resolver) = &mut self.resolver {
+            resolver.process(ev);
+        }
+
+        let mut stats = DamageStats::default();
+        let skill_id = match ev.kind() {
+            CombatEventKind::Strike(strike) => {
+                let Ok(result) = strike.result() else {
+                    return;
+                };
+                if !result.is_hit() {
+                    return;
+                }
+                stats.barrier_damage = strike.barrier_damage() as i64;
+                stats.strike_damage = strike.damage() as i64 - stats.barrier_damage;
+                stats.hits = 1;
+                stats.crits = (result == StrikeResult::Crit) as u32;
+                stats.glances = (result == StrikeResult::Glance) as u32;
+                stats.flanking = strike.is_flanking() as u32;
+                strike.skill_id()
+            }
+            // negative damage is healing
+            CombatEventKind::BuffDamage(buff) if buff.raw_result() == 0 && buff.damage() > 0 => {
+                stats.barrier_damage = buff.barrier_damage() as i64;
+                stats.condition_damage = buff.damage() as i64 - stats.barrier_damage;
+                buff.buff_id()
+            }
+            _ => return,
+        };
+
+        let src = self
+            .resolver
+            .as_ref()
+            .map_or(ev.src_agent, |resolver| resolver.owner(ev.src_agent));
+        let key = DamageKey {
+            src,
+            dst: ev.dst_agent,
+            skill_id,
+        };
+        *self.stats.entry(key).or_default() += &stats;
+
+        let start = *self.start.get_or_insert(ev.time);
+        self.end = self.end.max(ev.time);
+        let bucket = (ev.time.saturating_sub(start) / self.window) as usize;
+        let series = self.series.entry(src).or_default();
+        if series.len() <= bucket {
+            series.resize(bucket + 1, 0);
+        }
+        series[bucket] += stats.total_damage();
+    }
+
+    /// Iterates over the totals of all combinations of source, target and
+    /// skill.
+    pub fn iter(&self) -> impl Iterator<Item = (&DamageKey, &DamageStats)> {
+        self.stats.iter()
+    }
+
+    /// Sums up all totals that match the filter.
+    pub fn total(&self, mut filter: impl FnMut(&DamageKey) -> bool) -> DamageStats {
+        let mut total = DamageStats::default();
+        for (_, stats) in self.stats.iter().filter(|(key, _)| filter(key)) {
+            total += stats;
+        }
+        total
+    }
+
+    /// Damage dealt by `src`.
+    pub fn source_total(&self, src: usize) -> DamageStats {
+        self.total(|key| key.src == src)
+    }
+
+    /// Damage received by `dst`.
+    pub fn target_total(&self, dst: usize) -> DamageStats {
+        self.total(|key| key.dst == dst)
+    }
+
+    /// Damage dealt by `src`, per skill.
+    pub fn skill_totals(&self, src: usize) -> HashMap<u32, DamageStats> {
+        let mut skills = HashMap::<_, DamageStats>::new();
+        for (key, stats) in self.stats.iter().filter(|(key, _)| key.src == src) {
+            *skills.entry(key.skill_id).or_default() += stats;
+        }
+        skills
+    }
+
+    /// Damage dealt per source.
+    pub fn source_totals(&self) -> HashMap<usize, DamageStats> {
+        let mut sources = HashMap::<_, DamageStats>::new();
+        for (key, stats) in &self.stats {
+            *sources.entry(key.src).or_default() += stats;
+        }
+        sources
+    }
+
+    /// Milliseconds between the first and the last damage event.
+    pub fn duration(&self) -> u64 {
+        self.start.map_or(0, |start| self.end - start)
+    }
+
+    /// Damage per second of `src` over the whole fight, including damage
+    /// absorbed by barrier.
+    pub fn dps(&self, src: usize) -> f64 {
+        let stats = self.source_total(src);
+        let seconds = self.duration().max(1000) as f64 / 1000.0;
+        stats.total_damage() as f64 / seconds
+    }
+
+    /// Damage per second of `src` in each window since the first damage
+    /// event, including damage absorbed by barrier.
+    pub fn dps_series(&self, src: usize) -> Vec<f64> {
+        let windows = self.duration() / self.window + 1;
+        let seconds = self.window as f64 / 1000.0;
+        let series = self.series.get(&src).map_or(&[][..], Vec::as_slice);
+        (0..windows as usize)
+            .map(|i| series.get(i).copied().unwrap_or_default() as f64 / seconds)
+            .collect()
+    }
+
+    /// Removes all damage, e.g. when a new fight starts.
+    pub fn clear(&mut self) {
+        self.stats.clear();
+        self.series.clear();
+        self.start = None;
+        self.end = 0;
+        if let Some(resolver) = &mut self.resolver {
+            resolver.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::test_events::{buff_damage, events, strike};
+
+    const PLAYER: usize = 0x10;
+    const MINION: usize = 0x20;
+    const BOSS: usize = 0x30;
+    const BURNING: u32 = 737;
+
+    #[test]
+    fn counts_results() {
+        let mut damage = DamageAggregator::new();
+        events(&[
+            strike(0, PLAYER, BOSS, 1000, StrikeResult::Crit).skill_id(100),
+            strike(100, PLAYER, BOSS, 500, StrikeResult::Glance).skill_id(100),
+            strike(200, PLAYER, BOSS, 700, StrikeResult::Normal)
+                .skill_id(100)
+                .is_flanking(true),
+            strike(300, PLAYER, BOSS, 0, StrikeResult::Block).skill_id(100),
+        ])
+        .for_each(|ev| damage.add_event(&ev));
+        let stats = damage.source_total(PLAYER);
+        assert_eq!(stats.strike_damage, 2200);
+        assert_eq!(stats.hits, 3);
+        assert_eq!(stats.crits, 1);
+        assert_eq!(stats.glances, 1);
+        assert_eq!(stats.flanking, 1);
+        assert_eq!(damage.skill_totals(PLAYER)[&100].hits, 3);
+    }
+
+    #[test]
+    fn splits_barrier() {
+        let mut damage = DamageAggregator::new();
+        events(&[
+            strike(0, PLAYER, BOSS, 1000, StrikeResult::Normal)
+                .overstack_value(300)
+                .is_shields(true),
+            // overstack_value without is_shields is not barrier
+            strike(1000, PLAYER, BOSS, 1000, StrikeResult::Normal).overstack_value(300),
+            buff_damage(2000, PLAYER, BOSS, BURNING, 200)
+                .overstack_value(50)
+                .is_shields(true),
+        ])
+        .for_each(|ev| damage.add_event(&ev));
+        let stats = damage.target_total(BOSS);
+        assert_eq!(stats.strike_damage, 1700);
+        assert_eq!(stats.condition_damage, 150);
+        assert_eq!(stats.barrier_damage, 350);
+        assert_eq!(stats.total_damage(), 2200);
+        assert_eq!(damage.duration(), 2000);
+        assert_eq!(damage.dps(PLAYER), 1100.0);
+        assert_eq!(damage.dps_series(PLAYER), vec![1000.0, 1000.0, 200.0]);
+    }
+
+    #[test]
+    fn ignores_heals() {
+        let mut damage = DamageAggregator::new();
+        events(&[
+            buff_damage(0, PLAYER, BOSS, BURNING, 150),
+            buff_damage(1000, PLAYER, BOSS, BURNING, -400),
+            buff_damage(2000, PLAYER, BOSS, BURNING, 0),
+        ])
+        .for_each(|ev| damage.add_event(&ev));
+        let stats = damage.source_total(PLAYER);
+        assert_eq!(stats.condition_damage, 150);
+        assert_eq!(stats.hits, 0);
+    }
+
+    #[test]
+    fn attributes_minions() {
+        let minion = [
+            strike(0, PLAYER, BOSS, 100, StrikeResult::Normal),
+            strike(1000, MINION, BOSS, 400, StrikeResult::Normal)
+                .src_master_instance_id(PLAYER as u16),
+        ];
+
+        let mut damage = DamageAggregator::new();
+        events(&minion).for_each(|ev| damage.add_event(&ev));
+        assert_eq!(damage.source_total(PLAYER).strike_damage, 500);
+        assert_eq!(damage.source_total(MINION), DamageStats::default());
+
+        let mut damage = DamageAggregator::new().attribute_minions(false);
+        events(&minion).for_each(|ev| damage.add_event(&ev));
+        assert_eq!(damage.source_total(MINION).strike_damage, 400);
+    }
+}
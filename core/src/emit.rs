@@ -0,0 +1,42 @@
+use crate::{e10, e9, runtime, CombatEvent};
+
+/// Adds an event to the area combat of arcdps, signed with the `sig` of the
+/// plugin. arcdps logs the event and passes it to the combat callbacks of
+/// all plugins. Returns `false` if the plugin is not loaded or arcdps doesn't
+/// provide the export.
+///
+/// ```no_run
+/// use arcdps::{emit_area, CombatEventBuilder, StateChange};
+///
+/// const MY_MARKER: u32 = 1;
+///
+/// fn mark(time: u64, agent: usize) {
+///     let ev = CombatEventBuilder::new()
+///         .time(time)
+///         .src_agent(agent)
+///         .state_change(StateChange::Extension)
+///         .skill_id(MY_MARKER)
+///         .build();
+///     emit_area(&ev);
+/// }
+/// ```
+pub fn emit_area(ev: &CombatEvent) -> bool {
+    if runtime::export(9).is_null() {
+        return false;
+    }
+    // SAFETY: e9 only reads the event and arcdps copies it before returning,
+    // e9 does nothing if the plugin got released in the meantime
+    unsafe { e9(ev, runtime::sig()) };
+    true
+}
+
+/// Like [emit_area], but arcdps adds the event to the local combat and does
+/// not pass it to the callbacks of the calling plugin.
+pub fn emit_local(ev: &CombatEvent) -> bool {
+    if runtime::export(10).is_null() {
+        return false;
+    }
+    // SAFETY: see emit_area
+    unsafe { e10(ev, runtime::sig()) };
+    true
+}
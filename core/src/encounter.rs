@@ -0,0 +1,511 @@
+use crate::{
+    helpers::CombatEventArgs, Agent, AgentKind, CombatEvent, CombatEventKind, StateChange,
+    StateChangeEvent,
+};
+
+/// The kind of content an encounter belongs to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum EncounterKind {
+    Raid,
+    Strike,
+    Fractal,
+}
+
+/// A boss fight that [EncounterTracker] knows about.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EncounterInfo {
+    pub name: &'static str,
+    pub kind: EncounterKind,
+    /// Species ids of the bosses. The fight is won once all of them were seen
+    /// and every boss despawned or died, with at least one death.
+    pub species_ids: &'static [u16],
+}
+
+impl EncounterInfo {
+    const fn new(name: &'static str, kind: EncounterKind, species_ids: &'static [u16]) -> Self {
+        Self {
+            name,
+            kind,
+            species_ids,
+        }
+    }
+
+    /// Returns the encounter a boss species belongs to.
+    pub fn from_species_id(species_id: u16) -> Option<&'static EncounterInfo> {
+        ENCOUNTERS
+            .iter()
+            .find(|encounter| encounter.species_ids.contains(&species_id))
+    }
+}
+
+/// All encounters that [EncounterTracker] detects.
+///
+/// Encounters whose boss is a gadget, like Harvest Temple, can't be told
+/// apart by species and are missing. So are the Lonely Tower fractal and the
+/// encounters of Mount Balrior beyond Wing 8.
+pub static ENCOUNTERS: &[EncounterInfo] = {
+    use EncounterKind::*;
+    &[
+        EncounterInfo::new("Vale Guardian", Raid, &[15438]),
+        EncounterInfo::new("Gorseval the Multifarious", Raid, &[15429]),
+        EncounterInfo::new("Sabetha the Saboteur", Raid, &[15375]),
+        EncounterInfo::new("Slothasor", Raid, &[16123]),
+        EncounterInfo::new("Matthias Gabrel", Raid, &[16115]),
+        EncounterInfo::new("Keep Construct", Raid, &[16235]),
+        EncounterInfo::new("Xera", Raid, &[16246, 16286]),
+        EncounterInfo::new("Cairn the Indomitable", Raid, &[17194]),
+        EncounterInfo::new("Mursaat Overseer", Raid, &[17172]),
+        EncounterInfo::new("Samarog", Raid, &[17188]),
+        EncounterInfo::new("Deimos", Raid, &[17154]),
+        EncounterInfo::new("Soulless Horror", Raid, &[19767]),
+        EncounterInfo::new("Dhuum", Raid, &[19450]),
+        EncounterInfo::new("Conjured Amalgamate", Raid, &[43974]),
+        EncounterInfo::new("Twin Largos", Raid, &[21105, 21089]),
+        EncounterInfo::new("Qadim", Raid, &[20934]),
+        EncounterInfo::new("Cardinal Adina", Raid, &[22006]),
+        EncounterInfo::new("Cardinal Sabir", Raid, &[21964]),
+        EncounterInfo::new("Qadim the Peerless", Raid, &[22000]),
+        EncounterInfo::new("Greer, the Blightbringer", Raid, &[26725]),
+        EncounterInfo::new("Decima, the Stormsinger", Raid, &[26774]),
+        EncounterInfo::new("Decima, the Stormsinger (CM)", Raid, &[26867]),
+        EncounterInfo::new("Ura, the Steamshrieker", Raid, &[26712]),
+        EncounterInfo::new("Icebrood Construct", Strike, &[22154]),
+        EncounterInfo::new(
+            "Voice and Claw of the Fallen",
+            Strike,
+            &[22343, 22481, 22315],
+        ),
+        EncounterInfo::new("Fraenir of Jormag", Strike, &[22492]),
+        EncounterInfo::new("Boneskinner", Strike, &[22521]),
+        EncounterInfo::new("Whisper of Jormag", Strike, &[22711]),
+        EncounterInfo::new("Cold War", Strike, &[22836]),
+        EncounterInfo::new("Aetherblade Hideout", Strike, &[24033]),
+        EncounterInfo::new("Xunlai Jade Junkyard", Strike, &[23957]),
+        EncounterInfo::new("Kaineng Overlook", Strike, &[24485]),
+        EncounterInfo::new("Old Lion's Court", Strike, &[25413, 25415, 25419]),
+        EncounterInfo::new("Old Lion's Court (CM)", Strike, &[25414, 25416, 25423]),
+        EncounterInfo::new("Cosmic Observatory", Strike, &[25705]),
+        EncounterInfo::new("Temple of Febe", Strike, &[25989]),
+        EncounterInfo::new("MAMA", Fractal, &[17021]),
+        EncounterInfo::new("Siax the Corrupted", Fractal, &[17028]),
+        EncounterInfo::new("Ensolyss of the Endless Torment", Fractal, &[16948]),
+        EncounterInfo::new("Skorvald the Shattered", Fractal, &[17632]),
+        EncounterInfo::new("Artsariiv", Fractal, &[17949]),
+        EncounterInfo::new("Arkk", Fractal, &[17759]),
+        EncounterInfo::new("Ai, Keeper of the Peak", Fractal, &[23254]),
+        EncounterInfo::new("Kanaxai, Scythe of House Aurkus", Fractal, &[25577]),
+    ]
+};
+
+/// A change of the encounter, returned by [`EncounterTracker::process`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum EncounterEvent {
+    Started {
+        info: &'static EncounterInfo,
+        time: u64,
+    },
+    Ended {
+        info: &'static EncounterInfo,
+        time: u64,
+        /// Milliseconds since the start.
+        duration: u64,
+        success: bool,
+    },
+}
+
+/// A boss of the [ActiveEncounter].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Boss {
+    pub id: usize,
+    pub species_id: u16,
+    /// Last known health in percent, from 0 to 100.
+    pub health: f32,
+    pub dead: bool,
+    pub despawned: bool,
+}
+
+/// The encounter that is currently fought.
+#[derive(Debug, Clone)]
+pub struct ActiveEncounter {
+    pub info: &'static EncounterInfo,
+    pub start: u64,
+    /// The bosses that were seen so far.
+    pub bosses: Vec<Boss>,
+    success: bool,
+}
+
+impl ActiveEncounter {
+    /// Returns the lowest health of the bosses that are alive in percent.
+    pub fn boss_health(&self) -> Option<f32> {
+        self.bosses
+            .iter()
+            .filter(|boss| !boss.dead && !boss.despawned)
+            .map(|boss| boss.health)
+            .reduce(f32::min)
+    }
+
+    fn boss_mut(&mut self, id: usize) -> Option<&mut Boss> {
+        self.bosses.iter_mut().find(|boss| boss.id == id)
+    }
+
+    /// Adds the agents that are bosses of the encounter and weren't seen yet.
+    fn observe(&mut self, agents: [Option<Agent>; 2]) {
+        for agent in agents.into_iter().flatten() {
+            let Some(species_id) = species_id(&agent) else {
+                continue;
+            };
+            if self.info.species_ids.contains(&species_id) && self.boss_mut(agent.id).is_none() {
+                self.bosses.push(Boss {
+                    id: agent.id,
+                    species_id,
+                    health: 100.0,
+                    dead: false,
+                    despawned: false,
+                });
+            }
+        }
+    }
+
+    /// Whether every boss of the encounter was seen and none is left.
+    fn is_over(&self) -> bool {
+        self.info
+            .species_ids
+            .iter()
+            .all(|id| self.bosses.iter().any(|boss| boss.species_id == *id))
+            && self.bosses.iter().all(|boss| boss.dead || boss.despawned)
+    }
+
+    fn is_won(&self) -> bool {
+        self.is_over() && self.bosses.iter().any(|boss| boss.dead)
+    }
+}
+
+/// Detects the start and end of boss fights from the combat events.
+///
+/// A fight starts when a boss of [ENCOUNTERS] enters combat, deals or takes
+/// damage, or with a log start for one of them. It ends as a success with a
+/// reward or once all bosses died, and as a failure when the log ends, all
+/// bosses despawn or the local player leaves combat.
+///
+/// After a failure, the encounter only starts again once it was reset, that
+/// is a boss left combat or is back at full health, a new log started or
+/// [`EncounterTracker::reset`] was called.
+///
+/// ```
+/// use arcdps::{helpers::CombatEventArgs, EncounterEvent, EncounterTracker};
+///
+/// fn combat(tracker: &mut EncounterTracker, args: &CombatEventArgs) {
+///     match tracker.process(args) {
+///         Some(EncounterEvent::Started { info, .. }) => println!("{} started", info.name),
+///         Some(EncounterEvent::Ended { info, success, .. }) => {
+///             println!("{} ended, success: {}", info.name, success)
+///         }
+///         None => {}
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct EncounterTracker {
+    active: Option<ActiveEncounter>,
+    /// The encounter that failed last and wasn't reset yet.
+    failed: Option<&'static EncounterInfo>,
+}
+
+impl EncounterTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the encounter that is currently fought.
+    pub fn active(&self) -> Option<&ActiveEncounter> {
+        self.active.as_ref()
+    }
+
+    /// Updates the state with the arguments of a combat callback.
+    pub fn process(&mut self, args: &CombatEventArgs) -> Option<EncounterEvent> {
+        self.update(args.ev?, args.src, args.dst)
+    }
+
+    /// Updates the state with an event and its agents.
+    pub fn update(
+        &mut self,
+        ev: &CombatEvent,
+        src: Option<Agent>,
+        dst: Option<Agent>,
+    ) -> Option<EncounterEvent> {
+        let state = match ev.kind() {
+            CombatEventKind::StateChange(state) => Some(state),
+            _ => None,
+        };
+
+        let Some(active) = &mut self.active else {
+            self.check_reset(state, src);
+            return self.start(ev, state, src, dst);
+        };
+        active.observe([src, dst]);
+
+        let end = match state.map(|state| (state, state.state_change())) {
+            Some((_, StateChange::Reward)) => {
+                active.success = true;
+                true
+            }
+            Some((_, StateChange::ChangeDead)) => match active.boss_mut(ev.src_agent) {
+                Some(boss) => {
+                    boss.dead = true;
+                    active.success = active.is_won();
+                    active.success
+                }
+                None => false,
+            },
+            Some((state, StateChange::HealthPctUpdate)) => {
+                if let (Some(boss), Some(health)) =
+                    (active.boss_mut(ev.src_agent), state.health_percent())
+                {
+                    boss.health = health;
+                }
+                false
+            }
+            Some((_, StateChange::Despawn)) => match active.boss_mut(ev.src_agent) {
+                Some(boss) => {
+                    boss.despawned = true;
+                    active.success = active.is_won();
+                    active.is_over()
+                }
+                None => false,
+            },
+            Some((_, StateChange::ExitCombat)) => src.is_some_and(|src| src.self_ != 0),
+            Some((_, StateChange::SquadCombatEnd)) => true,
+            _ => false,
+        };
+        if !end {
+            return None;
+        }
+
+        let active = self.active.take()?;
+        if !active.success {
+            self.failed = Some(active.info);
+        }
+        Some(EncounterEvent::Ended {
+            info: active.info,
+            time: ev.time,
+            duration: ev.time.saturating_sub(active.start),
+            success: active.success,
+        })
+    }
+
+    /// Forgets a failed encounter once it was reset.
+    fn check_reset(&mut self, state: Option<StateChangeEvent>, src: Option<Agent>) {
+        let Some(failed) = self.failed else {
+            return;
+        };
+        let Some(state) = state else {
+            return;
+        };
+        let is_boss = src
+            .and_then(|src| species_id(&src))
+            .is_some_and(|id| failed.species_ids.contains(&id));
+        let reset = match state.state_change() {
+            StateChange::SquadCombatStart => true,
+            StateChange::ExitCombat => is_boss,
+            StateChange::HealthPctUpdate => {
+                is_boss && state.health_percent().is_some_and(|health| health >= 100.0)
+            }
+            _ => false,
+        };
+        if reset {
+            self.failed = None;
+        }
+    }
+
+    fn start(
+        &mut self,
+        ev: &CombatEvent,
+        state: Option<StateChangeEvent>,
+        src: Option<Agent>,
+        dst: Option<Agent>,
+    ) -> Option<EncounterEvent> {
+        let info = match state.map(|state| state.state_change()) {
+            Some(StateChange::SquadCombatStart) => {
+                EncounterInfo::from_species_id(ev.src_agent as u16)?
+            }
+            Some(StateChange::EnterCombat) => EncounterInfo::from_species_id(species_id(&src?)?)?,
+            None if is_damage(ev) => [src, dst]
+                .into_iter()
+                .flatten()
+                .find_map(|agent| EncounterInfo::from_species_id(species_id(&agent)?))?,
+            _ => return None,
+        };
+        if self.failed == Some(info) {
+            return None;
+        }
+        self.failed = None;
+
+        let mut active = ActiveEncounter {
+            info,
+            start: ev.time,
+            bosses: Vec::new(),
+            success: false,
+        };
+        active.observe([src, dst]);
+        self.active = Some(active);
+        Some(EncounterEvent::Started {
+            info,
+            time: ev.time,
+        })
+    }
+
+    /// Forgets the current encounter without ending it, and allows a failed
+    /// encounter to start again.
+    pub fn reset(&mut self) {
+        self.active = None;
+        self.failed = None;
+    }
+}
+
+/// Whether the event is a hit or a damaging buff tick.
+fn is_damage(ev: &CombatEvent) -> bool {
+    match ev.kind() {
+        CombatEventKind::Strike(strike) => {
+            strike.result().is_ok_and(|result| result.is_hit()) && strike.damage() > 0
+        }
+        CombatEventKind::BuffDamage(buff) => buff.raw_result() == 0 && buff.damage() > 0,
+        _ => false,
+    }
+}
+
+fn species_id(agent: &Agent) -> Option<u16> {
+    match agent.kind() {
+        AgentKind::Npc { species_id } => Some(species_id),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::test_events::{at, npc, player, state, strike},
+        Activation, StrikeResult,
+    };
+
+    const PLAYER: usize = 1;
+    const NIKARE: u16 = 21105;
+    const KENUT: u16 = 21089;
+    const VOICE: u16 = 22343;
+    const CLAW: u16 = 22481;
+    const MERGED: u16 = 22315;
+
+    fn hit(time: u64, dst: usize) -> CombatEvent {
+        strike(time, PLAYER, dst, 1000, StrikeResult::Normal).build()
+    }
+
+    #[test]
+    fn starts_on_combat_or_damage() {
+        let mut tracker = EncounterTracker::new();
+        let activation = at(0).activation(Activation::Start).build();
+        assert_eq!(
+            tracker.update(&activation, Some(npc(2, NIKARE)), None),
+            None
+        );
+        let evade = strike(0, PLAYER, 2, 0, StrikeResult::Evade).build();
+        assert_eq!(
+            tracker.update(&evade, Some(player(PLAYER)), Some(npc(2, NIKARE))),
+            None
+        );
+
+        let started = tracker.update(&hit(10, 2), Some(player(PLAYER)), Some(npc(2, NIKARE)));
+        assert!(matches!(
+            started,
+            Some(EncounterEvent::Started { info, time: 10 }) if info.name == "Twin Largos"
+        ));
+
+        let mut tracker = EncounterTracker::new();
+        let enter = state(20, StateChange::EnterCombat, 3).build();
+        assert!(tracker.update(&enter, Some(npc(3, KENUT)), None).is_some());
+        assert_eq!(tracker.active().unwrap().bosses[0].species_id, KENUT);
+    }
+
+    #[test]
+    fn tracks_all_bosses() {
+        let mut tracker = EncounterTracker::new();
+        tracker.update(&hit(0, 2), Some(player(PLAYER)), Some(npc(2, NIKARE)));
+
+        let health = state(0, StateChange::HealthPctUpdate, 2)
+            .dst_agent(5000)
+            .build();
+        assert_eq!(tracker.update(&health, Some(npc(2, NIKARE)), None), None);
+        assert_eq!(tracker.active().unwrap().boss_health(), Some(50.0));
+
+        let dead = state(100, StateChange::ChangeDead, 2).build();
+        assert_eq!(tracker.update(&dead, Some(npc(2, NIKARE)), None), None);
+
+        tracker.update(&hit(200, 3), Some(player(PLAYER)), Some(npc(3, KENUT)));
+        assert_eq!(tracker.active().unwrap().bosses.len(), 2);
+        let dead = state(300, StateChange::ChangeDead, 3).build();
+        assert!(matches!(
+            tracker.update(&dead, Some(npc(3, KENUT)), None),
+            Some(EncounterEvent::Ended {
+                duration: 300,
+                success: true,
+                ..
+            })
+        ));
+        assert!(tracker.active().is_none());
+
+        // Voice and Claw despawn when they merge, the fight goes on until the
+        // merged boss dies
+        let mut tracker = EncounterTracker::new();
+        tracker.update(&hit(0, 4), Some(player(PLAYER)), Some(npc(4, VOICE)));
+        tracker.update(&hit(0, 5), Some(player(PLAYER)), Some(npc(5, CLAW)));
+        for id in [4, 5] {
+            let despawn = state(100, StateChange::Despawn, id).build();
+            assert_eq!(tracker.update(&despawn, None, None), None);
+        }
+        tracker.update(&hit(200, 6), Some(player(PLAYER)), Some(npc(6, MERGED)));
+        let dead = state(300, StateChange::ChangeDead, 6).build();
+        assert!(matches!(
+            tracker.update(&dead, Some(npc(6, MERGED)), None),
+            Some(EncounterEvent::Ended { success: true, .. })
+        ));
+    }
+
+    #[test]
+    fn despawned_phases_win() {
+        let mut tracker = EncounterTracker::new();
+        tracker.update(&hit(0, 2), Some(player(PLAYER)), Some(npc(2, 16246)));
+        let despawn = state(100, StateChange::Despawn, 2).build();
+        assert_eq!(tracker.update(&despawn, Some(npc(2, 16246)), None), None);
+
+        tracker.update(&hit(200, 3), Some(player(PLAYER)), Some(npc(3, 16286)));
+        let dead = state(300, StateChange::ChangeDead, 3).build();
+        assert!(matches!(
+            tracker.update(&dead, Some(npc(3, 16286)), None),
+            Some(EncounterEvent::Ended { success: true, .. })
+        ));
+    }
+
+    #[test]
+    fn requires_reset_after_failure() {
+        let mut tracker = EncounterTracker::new();
+        tracker.update(&hit(0, 2), Some(player(PLAYER)), Some(npc(2, NIKARE)));
+        let exit = state(100, StateChange::ExitCombat, 1).build();
+        assert!(matches!(
+            tracker.update(&exit, Some(player(PLAYER)), None),
+            Some(EncounterEvent::Ended { success: false, .. })
+        ));
+
+        assert_eq!(
+            tracker.update(&hit(200, 2), Some(player(PLAYER)), Some(npc(2, NIKARE))),
+            None
+        );
+        let full = state(250, StateChange::HealthPctUpdate, 2)
+            .dst_agent(10000)
+            .build();
+        assert_eq!(tracker.update(&full, Some(npc(2, NIKARE)), None), None);
+        assert!(tracker
+            .update(&hit(300, 2), Some(player(PLAYER)), Some(npc(2, NIKARE)))
+            .is_some());
+    }
+}
@@ -0,0 +1,154 @@
+use crate::{Activation, BuffRemove, CombatEvent, Iff, StateChange, StrikeResult};
+
+/// Builds a [CombatEvent], e.g. to add it to the combat of arcdps with
+/// [emit_area](crate::emit_area). All fields that are not set are 0.
+///
+/// ```
+/// use arcdps::{CombatEventBuilder, StateChange};
+///
+/// let marker = CombatEventBuilder::new()
+///     .time(1000)
+///     .src_agent(42)
+///     .state_change(StateChange::Extension)
+///     .skill_id(7)
+///     .build();
+/// assert_eq!(marker.is_statechange, StateChange::Extension as u8);
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct CombatEventBuilder {
+    ev: CombatEvent,
+}
+
+impl Default for CombatEventBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+macro_rules! setters {
+    ($($(#[$meta:meta])* $field:ident: $ty:ty),* $(,)?) => {
+        $(
+            $(#[$meta])*
+            pub fn $field(mut self, $field: $ty) -> Self {
+                self.ev.$field = $field;
+                self
+            }
+        )*
+    };
+}
+
+macro_rules! flags {
+    ($($(#[$meta:meta])* $field:ident),* $(,)?) => {
+        $(
+            $(#[$meta])*
+            pub fn $field(mut self, $field: bool) -> Self {
+                self.ev.$field = $field as u8;
+                self
+            }
+        )*
+    };
+}
+
+impl CombatEventBuilder {
+    setters! {
+        /// Time in milliseconds, like `timeGetTime`.
+        time: u64,
+        src_agent: usize,
+        dst_agent: usize,
+        value: i32,
+        buff_dmg: i32,
+        overstack_value: u32,
+        skill_id: u32,
+        src_instance_id: u16,
+        dst_instance_id: u16,
+        src_master_instance_id: u16,
+        dst_master_instance_id: u16,
+    }
+
+    flags! {
+        buff,
+        is_ninety,
+        is_fifty,
+        is_moving,
+        is_flanking,
+        is_shields,
+        is_off_cycle,
+    }
+
+    pub fn new() -> Self {
+        Self {
+            ev: CombatEvent {
+                time: 0,
+                src_agent: 0,
+                dst_agent: 0,
+                value: 0,
+                buff_dmg: 0,
+                overstack_value: 0,
+                skill_id: 0,
+                src_instance_id: 0,
+                dst_instance_id: 0,
+                src_master_instance_id: 0,
+                dst_master_instance_id: 0,
+                iff: 0,
+                buff: 0,
+                result: 0,
+                is_activation: 0,
+                is_buff_remove: 0,
+                is_ninety: 0,
+                is_fifty: 0,
+                is_moving: 0,
+                is_statechange: 0,
+                is_flanking: 0,
+                is_shields: 0,
+                is_off_cycle: 0,
+                pad61: 0,
+                pad62: 0,
+                pad63: 0,
+                pad64: 0,
+            },
+        }
+    }
+
+    pub fn iff(mut self, iff: Iff) -> Self {
+        self.ev.iff = iff as u8;
+        self
+    }
+
+    /// Result of a strike.
+    pub fn result(mut self, result: StrikeResult) -> Self {
+        self.ev.result = result as u8;
+        self
+    }
+
+    /// Raw `result`, for results that are not a [StrikeResult], e.g. the
+    /// result of buff damage.
+    pub fn raw_result(mut self, result: u8) -> Self {
+        self.ev.result = result;
+        self
+    }
+
+    /// Stack id of buff apply and buff remove events, in `pad61` to `pad64`.
+    pub fn stack_id(mut self, stack_id: u32) -> Self {
+        [self.ev.pad61, self.ev.pad62, self.ev.pad63, self.ev.pad64] = stack_id.to_le_bytes();
+        self
+    }
+
+    pub fn activation(mut self, activation: Activation) -> Self {
+        self.ev.is_activation = activation as u8;
+        self
+    }
+
+    pub fn buff_remove(mut self, buff_remove: BuffRemove) -> Self {
+        self.ev.is_buff_remove = buff_remove as u8;
+        self
+    }
+
+    pub fn state_change(mut self, state_change: StateChange) -> Self {
+        self.ev.is_statechange = state_change as u8;
+        self
+    }
+
+    pub fn build(self) -> CombatEvent {
+        self.ev
+    }
+}
@@ -0,0 +1,236 @@
+use std::fmt::{Display, Formatter};
+
+/// Defines a `#[repr(u8)]` enum for a field of
+/// [CombatEvent](crate::CombatEvent) together with `TryFrom<u8>` and `Display`.
+macro_rules! field_enum {
+    (
+        $(#[$attr:meta])*
+        pub enum $name:ident {
+            $($(#[$var_attr:meta])* $variant:ident = $value:literal => $display:literal,)*
+        }
+    ) => {
+        $(#[$attr])*
+        #[repr(u8)]
+        #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+        #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+        pub enum $name {
+            $($(#[$var_attr])* $variant = $value,)*
+        }
+
+        impl TryFrom<u8> for $name {
+            type Error = u8;
+
+            /// Returns the value back if it is not known.
+            fn try_from(value: u8) -> Result<Self, Self::Error> {
+                match value {
+                    $($value => Ok(Self::$variant),)*
+                    _ => Err(value),
+                }
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                f.write_str(match self {
+                    $(Self::$variant => $display,)*
+                })
+            }
+        }
+    };
+}
+
+field_enum! {
+    /// The value of `iff`. Whether `src_agent` and `dst_agent` are hostile to
+    /// each other.
+    pub enum Iff {
+        Friend  = 0 => "Friend",
+        Foe     = 1 => "Foe",
+        Unknown = 2 => "Unknown",
+    }
+}
+
+field_enum! {
+    /// The value of `result` for a [StrikeEvent](crate::StrikeEvent).
+    pub enum StrikeResult {
+        Normal         = 0 => "Normal",
+        Crit           = 1 => "Critical",
+        Glance         = 2 => "Glancing",
+        Block          = 3 => "Blocked",
+        Evade          = 4 => "Evaded",
+        Interrupt      = 5 => "Interrupted",
+        Absorb         = 6 => "Absorbed",
+        Blind          = 7 => "Blinded",
+        /// The hit killed `dst_agent`.
+        KillingBlow    = 8 => "Killing Blow",
+        /// The hit downed `dst_agent`.
+        Downed         = 9 => "Downed",
+        /// `value` is the breakbar damage times 10.
+        BreakbarDamage = 10 => "Breakbar Damage",
+        /// An on-activation effect, no damage was dealt.
+        Activation     = 11 => "Activation",
+        /// `dst_agent` got crowd controlled.
+        CrowdControl   = 12 => "Crowd Control",
+    }
+}
+
+impl StrikeResult {
+    /// Whether the strike dealt damage.
+    pub fn is_hit(&self) -> bool {
+        matches!(
+            self,
+            Self::Normal | Self::Crit | Self::Glance | Self::KillingBlow | Self::Downed
+        )
+    }
+
+    /// Whether the strike was avoided by `dst_agent`.
+    pub fn is_avoided(&self) -> bool {
+        matches!(self, Self::Block | Self::Evade | Self::Absorb | Self::Blind)
+    }
+}
+
+field_enum! {
+    /// The value of `is_activation`.
+    pub enum Activation {
+        None            = 0 => "None",
+        /// The cast started.
+        Start           = 1 => "Start",
+        /// Unused, quickness is included in the duration of
+        /// [`Activation::Start`].
+        QuicknessUnused = 2 => "Quickness",
+        /// The cast was stopped after the skill was fired.
+        CancelFire      = 3 => "Cancel Fire",
+        /// The cast was stopped before the skill was fired.
+        CancelCancel    = 4 => "Cancel Cancel",
+        /// The animation completed fully.
+        Reset           = 5 => "Reset",
+    }
+}
+
+impl Activation {
+    /// Whether the cast started.
+    pub fn is_start(&self) -> bool {
+        matches!(self, Self::Start | Self::QuicknessUnused)
+    }
+
+    /// Whether the cast was stopped before the animation completed.
+    pub fn is_cancel(&self) -> bool {
+        matches!(self, Self::CancelFire | Self::CancelCancel)
+    }
+
+    /// Whether the cast was stopped before the skill was fired.
+    pub fn is_interrupted(&self) -> bool {
+        *self == Self::CancelCancel
+    }
+}
+
+field_enum! {
+    /// The value of `is_buff_remove`.
+    pub enum BuffRemove {
+        None   = 0 => "None",
+        /// All stacks were removed.
+        All    = 1 => "All",
+        /// A single stack was removed.
+        Single = 2 => "Single",
+        /// A single stack was removed by arcdps, because the server did not
+        /// report the removal.
+        Manual = 3 => "Manual",
+    }
+}
+
+impl BuffRemove {
+    /// Whether only one stack was removed.
+    pub fn is_single(&self) -> bool {
+        matches!(self, Self::Single | Self::Manual)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that every known value converts back to itself and returns the
+    /// number of known values.
+    fn round_trip<T: TryFrom<u8, Error = u8>>(to_u8: impl Fn(T) -> u8) -> usize {
+        (0..=u8::MAX)
+            .filter(|&value| match T::try_from(value) {
+                Ok(field) => {
+                    assert_eq!(to_u8(field), value);
+                    true
+                }
+                Err(err) => {
+                    assert_eq!(err, value);
+                    false
+                }
+            })
+            .count()
+    }
+
+    #[test]
+    fn converts_from_u8() {
+        assert_eq!(round_trip(|field: Iff| field as u8), 3);
+        assert_eq!(round_trip(|field: StrikeResult| field as u8), 13);
+        assert_eq!(round_trip(|field: Activation| field as u8), 6);
+        assert_eq!(round_trip(|field: BuffRemove| field as u8), 4);
+
+        assert_eq!(Iff::try_from(1), Ok(Iff::Foe));
+        assert_eq!(StrikeResult::try_from(13), Err(13));
+        assert_eq!(Activation::try_from(6), Err(6));
+        assert_eq!(BuffRemove::try_from(4), Err(4));
+    }
+
+    #[test]
+    fn displays_names() {
+        assert_eq!(Iff::Unknown.to_string(), "Unknown");
+        assert_eq!(StrikeResult::Crit.to_string(), "Critical");
+        assert_eq!(StrikeResult::KillingBlow.to_string(), "Killing Blow");
+        assert_eq!(Activation::CancelFire.to_string(), "Cancel Fire");
+        assert_eq!(BuffRemove::Single.to_string(), "Single");
+    }
+
+    #[test]
+    fn strike_result_groups() {
+        let hits = [
+            StrikeResult::Normal,
+            StrikeResult::Crit,
+            StrikeResult::Glance,
+            StrikeResult::KillingBlow,
+            StrikeResult::Downed,
+        ];
+        let avoided = [
+            StrikeResult::Block,
+            StrikeResult::Evade,
+            StrikeResult::Absorb,
+            StrikeResult::Blind,
+        ];
+        for value in 0..=u8::MAX {
+            let Ok(result) = StrikeResult::try_from(value) else {
+                continue;
+            };
+            assert_eq!(result.is_hit(), hits.contains(&result), "{result}");
+            assert_eq!(result.is_avoided(), avoided.contains(&result), "{result}");
+        }
+    }
+
+    #[test]
+    fn activation_groups() {
+        assert!(Activation::Start.is_start());
+        assert!(Activation::QuicknessUnused.is_start());
+        assert!(!Activation::Reset.is_start());
+
+        assert!(Activation::CancelFire.is_cancel());
+        assert!(Activation::CancelCancel.is_cancel());
+        assert!(!Activation::Reset.is_cancel());
+        assert!(!Activation::Start.is_cancel());
+
+        assert!(Activation::CancelCancel.is_interrupted());
+        assert!(!Activation::CancelFire.is_interrupted());
+    }
+
+    #[test]
+    fn buff_remove_groups() {
+        assert!(BuffRemove::Single.is_single());
+        assert!(BuffRemove::Manual.is_single());
+        assert!(!BuffRemove::All.is_single());
+        assert!(!BuffRemove::None.is_single());
+    }
+}
@@ -0,0 +1,396 @@
+//! Typed decoding of [CombatEvent]s.
+//!
+//! The meaning of the fields of a [CombatEvent] depends on the kind of the
+//! event. [`CombatEvent::kind`] classifies an event by the rules of the
+//! arcdps readme and returns a view that only exposes the fields that are
+//! valid for that kind.
+mod builder;
+mod fields;
+mod revision;
+mod state_change;
+#[cfg(test)]
+pub(crate) mod test_events;
+
+use std::ops::Deref;
+
+pub use builder::*;
+pub use fields::*;
+pub use revision::*;
+pub use state_change::*;
+
+use crate::CombatEvent;
+
+/// The kind of a [CombatEvent]. See [`CombatEvent::kind`].
+#[derive(Debug, Copy, Clone)]
+pub enum CombatEventKind<'a> {
+    /// `is_statechange` is set. The other fields depend on the
+    /// [StateChange].
+    StateChange(StateChangeEvent<'a>),
+    /// `is_activation` is set.
+    Activation(ActivationEvent<'a>),
+    /// `is_buff_remove` is set.
+    BuffRemove(BuffRemoveEvent<'a>),
+    /// `buff` and `value` are set.
+    BuffApply(BuffApplyEvent<'a>),
+    /// `buff` is set and `value` is zero. Includes condition damage and buffs
+    /// that heal.
+    BuffDamage(BuffDamageEvent<'a>),
+    /// None of the above. Direct damage of a skill.
+    Strike(StrikeEvent<'a>),
+    /// A state change that was added to arcdps after this crate was
+    /// released. Contains the value of `is_statechange`.
+    Unknown(u8),
+}
+
+impl CombatEvent {
+    /// Classifies the event. Does not copy the event.
+    /// ```
+    /// use arcdps::{CombatEvent, CombatEventKind};
+    ///
+    /// fn damage(ev: &CombatEvent) -> i32 {
+    ///     match ev.kind() {
+    ///         CombatEventKind::Strike(strike) => strike.damage(),
+    ///         CombatEventKind::BuffDamage(buff) => buff.damage(),
+    ///         _ => 0,
+    ///     }
+    /// }
+    /// ```
+    pub fn kind(&self) -> CombatEventKind<'_> {
+        if self.is_statechange != 0 {
+            match StateChange::try_from(self.is_statechange) {
+                Ok(_) => CombatEventKind::StateChange(StateChangeEvent(self)),
+                Err(unknown) => CombatEventKind::Unknown(unknown),
+            }
+        } else if self.is_activation != 0 {
+            CombatEventKind::Activation(ActivationEvent(self))
+        } else if self.is_buff_remove != 0 {
+            CombatEventKind::BuffRemove(BuffRemoveEvent(self))
+        } else if self.buff != 0 && self.value != 0 {
+            CombatEventKind::BuffApply(BuffApplyEvent(self))
+        } else if self.buff != 0 {
+            CombatEventKind::BuffDamage(BuffDamageEvent(self))
+        } else {
+            CombatEventKind::Strike(StrikeEvent(self))
+        }
+    }
+
+    /// The `u32` that is packed into the padding bytes. The stack id of buff
+    /// apply and buff remove events, and the `sig` of the plugin that added
+    /// the event with [emit_area](crate::emit_area) or
+    /// [emit_local](crate::emit_local).
+    pub(crate) fn pad_u32(&self) -> u32 {
+        u32::from_le_bytes([self.pad61, self.pad62, self.pad63, self.pad64])
+    }
+}
+
+macro_rules! event_view {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        /// Derefs to the underlying [CombatEvent].
+        #[derive(Debug, Copy, Clone)]
+        pub struct $name<'a>(&'a CombatEvent);
+
+        impl<'a> $name<'a> {
+            /// Returns the underlying event.
+            pub fn event(&self) -> &'a CombatEvent {
+                self.0
+            }
+        }
+
+        impl Deref for $name<'_> {
+            type Target = CombatEvent;
+
+            fn deref(&self) -> &Self::Target {
+                self.0
+            }
+        }
+    };
+}
+
+event_view!(
+    /// A state change. `src_agent` is the agent the state change is about.
+    StateChangeEvent
+);
+
+impl StateChangeEvent<'_> {
+    /// Returns the raw state change, `is_statechange`.
+    pub fn raw_state_change(&self) -> u8 {
+        self.0.is_statechange
+    }
+}
+
+event_view!(
+    /// A skill activation of `src_agent`.
+    ActivationEvent
+);
+
+impl ActivationEvent<'_> {
+    /// Returns the raw activation, `is_activation`.
+    pub fn raw_activation(&self) -> u8 {
+        self.0.is_activation
+    }
+
+    /// Returns the activation, or the raw value if it is not known.
+    pub fn activation(&self) -> Result<Activation, u8> {
+        Activation::try_from(self.0.is_activation)
+    }
+
+    pub fn skill_id(&self) -> u32 {
+        self.0.skill_id
+    }
+
+    /// For a start, the expected duration of the cast in milliseconds. For a
+    /// cancel or fire, the time that was spent casting.
+    pub fn duration(&self) -> i32 {
+        self.0.value
+    }
+
+    /// Like [`ActivationEvent::duration`], scaled by quickness and alacrity.
+    pub fn scaled_duration(&self) -> i32 {
+        self.0.buff_dmg
+    }
+}
+
+event_view!(
+    /// Buff `skill_id` was removed from `src_agent` by `dst_agent`.
+    BuffRemoveEvent
+);
+
+impl BuffRemoveEvent<'_> {
+    /// Returns the raw kind of removal, `is_buff_remove`.
+    pub fn raw_buff_remove(&self) -> u8 {
+        self.0.is_buff_remove
+    }
+
+    /// Returns the kind of removal, or the raw value if it is not known.
+    pub fn buff_remove(&self) -> Result<BuffRemove, u8> {
+        BuffRemove::try_from(self.0.is_buff_remove)
+    }
+
+    pub fn buff_id(&self) -> u32 {
+        self.0.skill_id
+    }
+
+    /// Duration that was removed in milliseconds.
+    pub fn removed_duration(&self) -> i32 {
+        self.0.value
+    }
+
+    /// Duration that was removed from intensity stacks in milliseconds.
+    pub fn removed_intensity(&self) -> i32 {
+        self.0.buff_dmg
+    }
+
+    /// The id of the removed stack. Only set when a single stack was removed.
+    pub fn stack_id(&self) -> u32 {
+        self.0.pad_u32()
+    }
+}
+
+event_view!(
+    /// Buff `skill_id` was applied to `dst_agent` by `src_agent`.
+    BuffApplyEvent
+);
+
+impl BuffApplyEvent<'_> {
+    pub fn buff_id(&self) -> u32 {
+        self.0.skill_id
+    }
+
+    /// Applied duration in milliseconds.
+    pub fn duration(&self) -> i32 {
+        self.0.value
+    }
+
+    /// Duration in milliseconds that was above the stack limit and got
+    /// dropped.
+    pub fn overstack(&self) -> u32 {
+        self.0.overstack_value
+    }
+
+    /// The id of the applied stack.
+    pub fn stack_id(&self) -> u32 {
+        self.0.pad_u32()
+    }
+
+    /// Whether the stack became active.
+    pub fn is_active(&self) -> bool {
+        self.0.is_shields != 0
+    }
+
+    /// Whether the existing stack [`BuffApplyEvent::stack_id`] was extended,
+    /// instead of a new stack being applied.
+    pub fn is_extension(&self) -> bool {
+        self.0.is_off_cycle != 0
+    }
+}
+
+event_view!(
+    /// Damage that buff `skill_id` of `src_agent` dealt to `dst_agent`.
+    BuffDamageEvent
+);
+
+impl BuffDamageEvent<'_> {
+    pub fn buff_id(&self) -> u32 {
+        self.0.skill_id
+    }
+
+    /// Damage that was dealt, including the part absorbed by barrier.
+    /// Negative for healing.
+    pub fn damage(&self) -> i32 {
+        self.0.buff_dmg
+    }
+
+    /// Part of the damage that was absorbed by barrier.
+    pub fn barrier_damage(&self) -> u32 {
+        if self.0.is_shields != 0 {
+            self.0.overstack_value
+        } else {
+            0
+        }
+    }
+
+    /// Returns the raw result, `result`. Zero if the damage was dealt.
+    pub fn raw_result(&self) -> u8 {
+        self.0.result
+    }
+
+    /// Whether the damage was not caused by the regular tick of the buff.
+    pub fn is_off_cycle(&self) -> bool {
+        self.0.is_off_cycle != 0
+    }
+}
+
+event_view!(
+    /// Direct damage that skill `skill_id` of `src_agent` dealt to
+    /// `dst_agent`.
+    StrikeEvent
+);
+
+impl StrikeEvent<'_> {
+    pub fn skill_id(&self) -> u32 {
+        self.0.skill_id
+    }
+
+    /// Damage that was dealt, including the part absorbed by barrier.
+    pub fn damage(&self) -> i32 {
+        self.0.value
+    }
+
+    /// Part of the damage that was absorbed by barrier.
+    pub fn barrier_damage(&self) -> u32 {
+        if self.0.is_shields != 0 {
+            self.0.overstack_value
+        } else {
+            0
+        }
+    }
+
+    /// Returns the raw result, `result`.
+    pub fn raw_result(&self) -> u8 {
+        self.0.result
+    }
+
+    /// Returns the result, or the raw value if it is not known.
+    pub fn result(&self) -> Result<StrikeResult, u8> {
+        StrikeResult::try_from(self.0.result)
+    }
+
+    pub fn is_flanking(&self) -> bool {
+        self.0.is_flanking != 0
+    }
+
+    /// Whether `src_agent` was above 90% health.
+    pub fn is_ninety(&self) -> bool {
+        self.0.is_ninety != 0
+    }
+
+    /// Whether `dst_agent` was below 50% health.
+    pub fn is_fifty(&self) -> bool {
+        self.0.is_fifty != 0
+    }
+
+    /// Whether `src_agent` was moving.
+    pub fn is_moving(&self) -> bool {
+        self.0.is_moving != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{test_events::*, *};
+
+    #[test]
+    fn kind_state_change() {
+        let ev = state(1, StateChange::EnterCombat, 2).build();
+        assert!(matches!(
+            ev.kind(),
+            CombatEventKind::StateChange(state_change)
+                if state_change.state_change() == StateChange::EnterCombat
+        ));
+    }
+
+    #[test]
+    fn kind_unknown_state_change() {
+        let mut ev = at(1).build();
+        ev.is_statechange = 200;
+        assert!(matches!(ev.kind(), CombatEventKind::Unknown(200)));
+    }
+
+    #[test]
+    fn kind_activation() {
+        // activations carry the skill id and sometimes the buff flag
+        let ev = at(1)
+            .skill_id(5492)
+            .buff(true)
+            .value(100)
+            .activation(Activation::Start)
+            .build();
+        assert!(matches!(
+            ev.kind(),
+            CombatEventKind::Activation(activation)
+                if activation.activation() == Ok(Activation::Start)
+        ));
+    }
+
+    #[test]
+    fn kind_buff_remove() {
+        let ev = buff_remove(1, 2, 740, BuffRemove::Single, 7)
+            .value(1000)
+            .build();
+        assert!(matches!(
+            ev.kind(),
+            CombatEventKind::BuffRemove(remove)
+                if remove.buff_remove() == Ok(BuffRemove::Single) && remove.stack_id() == 7
+        ));
+    }
+
+    #[test]
+    fn kind_buff_apply() {
+        let ev = buff_apply(1, 2, 3, 740, 7, 1000).build();
+        assert!(matches!(
+            ev.kind(),
+            CombatEventKind::BuffApply(apply)
+                if apply.buff_id() == 740 && apply.duration() == 1000 && apply.stack_id() == 7
+        ));
+    }
+
+    #[test]
+    fn kind_buff_damage() {
+        let ev = buff_damage(1, 2, 3, 736, 150).build();
+        assert!(matches!(
+            ev.kind(),
+            CombatEventKind::BuffDamage(damage) if damage.buff_id() == 736 && damage.damage() == 150
+        ));
+    }
+
+    #[test]
+    fn kind_strike() {
+        let ev = strike(1, 2, 3, 1234, StrikeResult::Crit).build();
+        assert!(matches!(
+            ev.kind(),
+            CombatEventKind::Strike(strike)
+                if strike.damage() == 1234 && strike.result() == Ok(StrikeResult::Crit)
+        ));
+    }
+}
@@ -0,0 +1,133 @@
+use std::{
+    borrow::Cow,
+    fmt::{Display, Formatter},
+};
+
+use crate::{CombatEvent, CombatEventRev0};
+
+/// The layout of a [CombatEvent] is not known to this crate. Contains the
+/// revision.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct UnsupportedRevision(pub u64);
+
+impl Display for UnsupportedRevision {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unsupported combat event revision {}", self.0)
+    }
+}
+
+impl std::error::Error for UnsupportedRevision {}
+
+impl CombatEvent {
+    /// Interprets the event in the layout of `revision` and converts it to the
+    /// current layout, which is revision 1. Does not copy revision 1 events.
+    ///
+    /// The safe combat callbacks of [arcdps_export!](crate::arcdps_export)
+    /// already receive normalized events.
+    pub fn normalized(&self, revision: u64) -> Result<Cow<'_, CombatEvent>, UnsupportedRevision> {
+        match revision {
+            0 => {
+                // SAFETY: both layouts are 64 bytes of plain integers without
+                // padding
+                let ev = unsafe { std::mem::transmute::<CombatEvent, CombatEventRev0>(*self) };
+                Ok(Cow::Owned((&ev).into()))
+            }
+            1 => Ok(Cow::Borrowed(self)),
+            _ => Err(UnsupportedRevision(revision)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CombatEventBuilder;
+
+    #[test]
+    fn normalizes_rev0() {
+        let rev0 = CombatEventRev0 {
+            time: 100,
+            src_agent: 1,
+            dst_agent: 2,
+            value: 1234,
+            buff_dmg: 5,
+            overstack_value: 6,
+            skill_id: 5492,
+            src_instance_id: 7,
+            dst_instance_id: 8,
+            src_master_instance_id: 9,
+            iss_offset: 0,
+            iss_offset_target: 0,
+            iss_bd_offset: 0,
+            iss_bd_offset_target: 0,
+            iss_alt_offset: 0,
+            iss_alt_offset_target: 0,
+            skar: 0,
+            skar_alt: 0,
+            skar_use_alt: 0,
+            iff: 1,
+            buff: 1,
+            result: 2,
+            is_activation: 3,
+            is_buff_remove: 4,
+            is_ninety: 1,
+            is_fifty: 1,
+            is_moving: 1,
+            is_statechange: 5,
+            is_flanking: 1,
+            is_shields: 1,
+            is_off_cycle: 1,
+            pad64: 0xFF,
+        };
+        // arcdps passes the old layout through the same pointer
+        // SAFETY: both layouts are 64 bytes of plain integers without padding
+        let raw = unsafe { std::mem::transmute::<CombatEventRev0, CombatEvent>(rev0) };
+
+        let ev = raw.normalized(0).unwrap();
+        assert!(matches!(ev, Cow::Owned(_)));
+        assert_eq!(
+            (ev.time, ev.src_agent, ev.dst_agent, ev.value, ev.buff_dmg),
+            (100, 1, 2, 1234, 5)
+        );
+        assert_eq!((ev.overstack_value, ev.skill_id), (6, 5492));
+        assert_eq!(
+            (
+                ev.src_instance_id,
+                ev.dst_instance_id,
+                ev.src_master_instance_id,
+                ev.dst_master_instance_id
+            ),
+            (7, 8, 9, 0)
+        );
+        assert_eq!(
+            (
+                ev.iff,
+                ev.buff,
+                ev.result,
+                ev.is_activation,
+                ev.is_buff_remove
+            ),
+            (1, 1, 2, 3, 4)
+        );
+        assert_eq!(
+            (ev.is_ninety, ev.is_fifty, ev.is_moving, ev.is_statechange),
+            (1, 1, 1, 5)
+        );
+        assert_eq!((ev.is_flanking, ev.is_shields, ev.is_off_cycle), (1, 1, 1));
+        assert_eq!(ev.pad_u32(), 0);
+    }
+
+    #[test]
+    fn borrows_rev1() {
+        let ev = CombatEventBuilder::new().value(3).build();
+        assert!(matches!(ev.normalized(1), Ok(Cow::Borrowed(normalized)) if normalized.value == 3));
+    }
+
+    #[test]
+    fn rejects_unknown_revisions() {
+        let ev = CombatEventBuilder::new().build();
+        let err = ev.normalized(2).unwrap_err();
+        assert_eq!(err, UnsupportedRevision(2));
+        assert_eq!(err.to_string(), "unsupported combat event revision 2");
+    }
+}
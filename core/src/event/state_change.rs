@@ -0,0 +1,424 @@
+use super::StateChangeEvent;
+
+/// The value of `is_statechange`. The documentation describes where the
+/// payload of the state change is stored; most of it is available through
+/// the accessors of [StateChangeEvent].
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum StateChange {
+    /// `src_agent` entered combat, `dst_agent` is the subgroup, `value` the
+    /// profession and `buff_dmg` the elite specialization.
+    EnterCombat        = 1,
+    /// `src_agent` left combat.
+    ExitCombat         = 2,
+    /// `src_agent` is alive.
+    ChangeUp           = 3,
+    /// `src_agent` is dead.
+    ChangeDead         = 4,
+    /// `src_agent` is downed.
+    ChangeDown         = 5,
+    /// `src_agent` is in game view.
+    Spawn              = 6,
+    /// `src_agent` left game view.
+    Despawn            = 7,
+    /// `dst_agent` is the health percentage of `src_agent` times 100.
+    HealthPctUpdate    = 8,
+    /// The log started. `value` is the server unix timestamp and `buff_dmg`
+    /// the local unix timestamp.
+    SquadCombatStart   = 9,
+    /// The log ended. Same payload as [`StateChange::SquadCombatStart`].
+    SquadCombatEnd     = 10,
+    /// `src_agent` swapped the weapon set to `dst_agent`, `value` is the
+    /// previous set.
+    WeapSwap           = 11,
+    /// `dst_agent` is the new max health of `src_agent`.
+    MaxHealthUpdate    = 12,
+    /// `src_agent` is the recording player.
+    PointOfView        = 13,
+    /// `src_agent` is the text language.
+    Language           = 14,
+    /// `src_agent` is the game build.
+    GwBuild            = 15,
+    /// `src_agent` is the server shard id.
+    ShardId            = 16,
+    /// `src_agent` received reward `dst_agent` of type `value`.
+    Reward             = 17,
+    /// A buff that was active when the log started, same payload as a buff
+    /// apply.
+    BuffInitial        = 18,
+    /// `dst_agent` packs the x and y position of `src_agent` as `f32`s and
+    /// `value` the z position.
+    Position           = 19,
+    /// Same payload as [`StateChange::Position`] for the velocity.
+    Velocity           = 20,
+    /// `dst_agent` packs the x and y facing direction of `src_agent` as
+    /// `f32`s.
+    Facing             = 21,
+    /// `src_agent` changed to team `dst_agent`, `value` is the previous team.
+    TeamChange         = 22,
+    /// `src_agent` is an attack target of gadget `dst_agent`, `value` is the
+    /// targetable state.
+    AttackTarget       = 23,
+    /// `dst_agent` is 1 if `src_agent` became targetable.
+    Targetable         = 24,
+    /// `src_agent` is the map id.
+    MapId              = 25,
+    /// Internal.
+    ReplInfo           = 26,
+    /// Stack `dst_agent` of a buff on `src_agent` became active.
+    StackActive        = 27,
+    /// Stack `pad61`-`pad64` of a buff on `src_agent` was reset to `value`.
+    StackReset         = 28,
+    /// `dst_agent` through `buff_dmg` are the guild guid of `src_agent`.
+    Guild              = 29,
+    /// Information about buff `skill_id`, see [`StateChangeEvent::buff_info`].
+    BuffInfo           = 30,
+    /// Formula of buff `skill_id`, stored as `f32`s starting at `time`.
+    BuffFormula        = 31,
+    /// Information about skill `skill_id`, stored as `f32`s starting at
+    /// `time`.
+    SkillInfo          = 32,
+    /// Action `src_agent` of skill `skill_id` happens at `dst_agent`
+    /// milliseconds.
+    SkillTiming        = 33,
+    /// `value` is the breakbar state of `src_agent`.
+    BreakbarState      = 34,
+    /// `value` is the breakbar percentage of `src_agent` as `f32`.
+    BreakbarPercent    = 35,
+    /// Log integrity message, stored as a string starting at `time`.
+    Integrity          = 36,
+    /// Marker `value` was added to `src_agent`.
+    Marker             = 37,
+    /// `dst_agent` is the barrier percentage of `src_agent` times 100.
+    BarrierPctUpdate   = 38,
+    /// `src_agent` is the species id of the target whose stats got reset.
+    StatReset          = 39,
+    /// Event of an arcdps extension.
+    Extension          = 40,
+    /// Event that will arrive delayed because the api is not ready yet.
+    ApiDelayed         = 41,
+    /// `src_agent` is the time in milliseconds when the instance likely
+    /// started.
+    InstanceStart      = 42,
+    /// `src_agent` is the tick rate of the server.
+    RateHealth         = 43,
+    /// Retired, not used since 240529.
+    Last90BeforeDown   = 44,
+    /// Retired, not used since 230716.
+    Effect             = 45,
+    /// `src_agent` and `dst_agent` are a guid that is identified by
+    /// `skill_id`.
+    IdToGuid           = 46,
+    /// `src_agent` is the species id of `dst_agent`, which is tracked by the
+    /// log.
+    LogNpcUpdate       = 47,
+    /// Internal.
+    IdleEvent          = 48,
+    /// Event of an arcdps extension that is passed to the combat callbacks.
+    ExtensionCombat    = 49,
+    /// `src_agent` is the fractal scale.
+    FractalScale       = 50,
+    /// An effect was created on the ground.
+    EffectGroundCreate = 51,
+    /// `src_agent` is the ruleset, see [`StateChangeEvent::ruleset`].
+    Ruleset            = 52,
+    /// Squad marker `skill_id` was placed at the position packed into
+    /// `src_agent` and `value`.
+    SquadMarker        = 53,
+    /// `src_agent` is the arcdps build, only in logs.
+    ArcBuild           = 54,
+    /// `value` is 1 if `src_agent` deployed a glider and 0 if it was stowed.
+    Glider             = 55,
+    /// `value` is the remaining duration of the stun `src_agent` broke.
+    StunBreak          = 56,
+    /// A missile was created.
+    MissileCreate      = 57,
+    /// A missile was launched.
+    MissileLaunch      = 58,
+    /// A missile was removed.
+    MissileRemove      = 59,
+    /// An effect was removed from the ground.
+    EffectGroundRemove = 60,
+    /// An effect was created on an agent.
+    EffectAgentCreate  = 61,
+    /// An effect was removed from an agent.
+    EffectAgentRemove  = 62,
+    /// The instance id of `src_agent` changed.
+    InstanceIdChange   = 63,
+    /// `src_agent` is the new map id.
+    MapChange          = 64,
+}
+
+impl TryFrom<u8> for StateChange {
+    type Error = u8;
+
+    /// Returns the value back if it is not a known state change.
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            // SAFETY: the range matches exactly the discriminants of the enum
+            1..=64 => Ok(unsafe { std::mem::transmute::<u8, StateChange>(value) }),
+            _ => Err(value),
+        }
+    }
+}
+
+/// Payload of [`StateChange::Reward`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Reward {
+    pub id: u64,
+    pub kind: i32,
+}
+
+/// Payload of [`StateChange::Marker`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Marker {
+    /// Id of the marker, 0 if the marker was removed.
+    pub id: i32,
+    pub is_commander_tag: bool,
+}
+
+/// Payload of [`StateChange::BuffInfo`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct BuffInfo {
+    pub buff_id: u32,
+    pub category: u8,
+    pub stacking_type: u8,
+    pub max_stacks: u16,
+    pub duration_cap: u32,
+    pub is_invulnerability: bool,
+    pub is_invert: bool,
+    pub is_resistance: bool,
+}
+
+/// Payload of [`StateChange::SquadMarker`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct SquadMarker {
+    /// Position of the marker. All components are infinite if the marker was
+    /// removed.
+    pub position: [f32; 3],
+    pub index: u32,
+}
+
+/// Returns the two `f32`s packed into an agent field.
+fn unpack_floats(packed: usize) -> [f32; 2] {
+    let bytes = (packed as u64).to_le_bytes();
+    [
+        f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        f32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+    ]
+}
+
+/// Returns a `float[3]` that starts at an agent field, with the third float
+/// in the low 4 bytes of the next field.
+fn unpack_float3(packed: usize, next: u32) -> [f32; 3] {
+    let [x, y] = unpack_floats(packed);
+    [x, y, f32::from_bits(next)]
+}
+
+impl StateChangeEvent<'_> {
+    /// Returns the state change.
+    pub fn state_change(&self) -> StateChange {
+        // CombatEvent::kind only creates the view for known state changes
+        StateChange::try_from(self.0.is_statechange).expect("unknown state change")
+    }
+
+    /// Returns the payload if the state change is one of `kinds`.
+    fn payload<T>(&self, kinds: &[StateChange], f: impl FnOnce() -> T) -> Option<T> {
+        kinds.contains(&self.state_change()).then(f)
+    }
+
+    /// Subgroup of [`StateChange::EnterCombat`].
+    pub fn subgroup(&self) -> Option<usize> {
+        self.payload(&[StateChange::EnterCombat], || self.0.dst_agent)
+    }
+
+    /// Health percentage of [`StateChange::HealthPctUpdate`], from 0 to 100.
+    pub fn health_percent(&self) -> Option<f32> {
+        self.payload(&[StateChange::HealthPctUpdate], || {
+            self.0.dst_agent as f32 / 100.0
+        })
+    }
+
+    /// Barrier percentage of [`StateChange::BarrierPctUpdate`], from 0 to
+    /// 100.
+    pub fn barrier_percent(&self) -> Option<f32> {
+        self.payload(&[StateChange::BarrierPctUpdate], || {
+            self.0.dst_agent as f32 / 100.0
+        })
+    }
+
+    /// Server and local unix timestamps of [`StateChange::SquadCombatStart`]
+    /// and [`StateChange::SquadCombatEnd`].
+    pub fn timestamps(&self) -> Option<(u32, u32)> {
+        self.payload(
+            &[StateChange::SquadCombatStart, StateChange::SquadCombatEnd],
+            || (self.0.value as u32, self.0.buff_dmg as u32),
+        )
+    }
+
+    /// New weapon set of [`StateChange::WeapSwap`].
+    pub fn weapon_set(&self) -> Option<usize> {
+        self.payload(&[StateChange::WeapSwap], || self.0.dst_agent)
+    }
+
+    /// Max health of [`StateChange::MaxHealthUpdate`].
+    pub fn max_health(&self) -> Option<usize> {
+        self.payload(&[StateChange::MaxHealthUpdate], || self.0.dst_agent)
+    }
+
+    /// Payload of [`StateChange::Reward`].
+    pub fn reward(&self) -> Option<Reward> {
+        self.payload(&[StateChange::Reward], || Reward {
+            id: self.0.dst_agent as u64,
+            kind: self.0.value,
+        })
+    }
+
+    /// Position of [`StateChange::Position`] and velocity of
+    /// [`StateChange::Velocity`].
+    pub fn position(&self) -> Option<[f32; 3]> {
+        self.payload(&[StateChange::Position, StateChange::Velocity], || {
+            unpack_float3(self.0.dst_agent, self.0.value as u32)
+        })
+    }
+
+    /// Facing direction of [`StateChange::Facing`].
+    pub fn facing(&self) -> Option<[f32; 2]> {
+        self.payload(&[StateChange::Facing], || unpack_floats(self.0.dst_agent))
+    }
+
+    /// New team of [`StateChange::TeamChange`].
+    pub fn team(&self) -> Option<usize> {
+        self.payload(&[StateChange::TeamChange], || self.0.dst_agent)
+    }
+
+    /// Payload of [`StateChange::Targetable`].
+    pub fn is_targetable(&self) -> Option<bool> {
+        self.payload(&[StateChange::Targetable], || self.0.dst_agent != 0)
+    }
+
+    /// Map id of [`StateChange::MapId`] and [`StateChange::MapChange`].
+    pub fn map_id(&self) -> Option<usize> {
+        self.payload(&[StateChange::MapId, StateChange::MapChange], || {
+            self.0.src_agent
+        })
+    }
+
+    /// Language id of [`StateChange::Language`].
+    pub fn language(&self) -> Option<usize> {
+        self.payload(&[StateChange::Language], || self.0.src_agent)
+    }
+
+    /// Game build of [`StateChange::GwBuild`].
+    pub fn gw_build(&self) -> Option<usize> {
+        self.payload(&[StateChange::GwBuild], || self.0.src_agent)
+    }
+
+    /// Shard id of [`StateChange::ShardId`].
+    pub fn shard_id(&self) -> Option<usize> {
+        self.payload(&[StateChange::ShardId], || self.0.src_agent)
+    }
+
+    /// Action and time in milliseconds of [`StateChange::SkillTiming`].
+    pub fn skill_timing(&self) -> Option<(usize, usize)> {
+        self.payload(&[StateChange::SkillTiming], || {
+            (self.0.src_agent, self.0.dst_agent)
+        })
+    }
+
+    /// State of [`StateChange::BreakbarState`].
+    pub fn breakbar_state(&self) -> Option<u16> {
+        self.payload(&[StateChange::BreakbarState], || self.0.value as u16)
+    }
+
+    /// Percentage of [`StateChange::BreakbarPercent`], from 0 to 1.
+    pub fn breakbar_percent(&self) -> Option<f32> {
+        self.payload(&[StateChange::BreakbarPercent], || {
+            f32::from_bits(self.0.value as u32)
+        })
+    }
+
+    /// Payload of [`StateChange::Marker`].
+    pub fn marker(&self) -> Option<Marker> {
+        self.payload(&[StateChange::Marker], || Marker {
+            id: self.0.value,
+            is_commander_tag: self.0.buff != 0,
+        })
+    }
+
+    /// Payload of [`StateChange::BuffInfo`].
+    pub fn buff_info(&self) -> Option<BuffInfo> {
+        self.payload(&[StateChange::BuffInfo], || BuffInfo {
+            buff_id: self.0.skill_id,
+            category: self.0.is_off_cycle,
+            stacking_type: self.0.pad61,
+            max_stacks: self.0.src_master_instance_id,
+            duration_cap: self.0.overstack_value,
+            is_invulnerability: self.0.is_flanking != 0,
+            is_invert: self.0.is_shields != 0,
+            is_resistance: self.0.pad62 != 0,
+        })
+    }
+
+    /// Ruleset bits of [`StateChange::Ruleset`]. 1 is PvE, 2 is WvW and 4 is
+    /// PvP.
+    pub fn ruleset(&self) -> Option<usize> {
+        self.payload(&[StateChange::Ruleset], || self.0.src_agent)
+    }
+
+    /// Payload of [`StateChange::SquadMarker`].
+    pub fn squad_marker(&self) -> Option<SquadMarker> {
+        self.payload(&[StateChange::SquadMarker], || SquadMarker {
+            position: unpack_float3(self.0.src_agent, self.0.dst_agent as u32),
+            index: self.0.skill_id,
+        })
+    }
+
+    /// Payload of [`StateChange::Glider`], true if the glider was deployed.
+    pub fn glider_deployed(&self) -> Option<bool> {
+        self.payload(&[StateChange::Glider], || self.0.value != 0)
+    }
+
+    /// Remaining duration in milliseconds of [`StateChange::StunBreak`].
+    pub fn stun_remaining(&self) -> Option<i32> {
+        self.payload(&[StateChange::StunBreak], || self.0.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        event::test_events::{state, state_change},
+        StateChange,
+    };
+
+    fn pack(x: f32, y: f32) -> usize {
+        (x.to_bits() as u64 | (y.to_bits() as u64) << 32) as usize
+    }
+
+    #[test]
+    fn position() {
+        let ev = state(0, StateChange::Position, 0)
+            .dst_agent(pack(1.5, -2.0))
+            .value(3.25f32.to_bits() as i32)
+            .build();
+        assert_eq!(state_change(&ev).position(), Some([1.5, -2.0, 3.25]));
+    }
+
+    #[test]
+    fn squad_marker() {
+        let ev = state(0, StateChange::SquadMarker, pack(10.0, 20.0))
+            .dst_agent(30.0f32.to_bits() as usize)
+            .value(99)
+            .skill_id(2)
+            .build();
+        let marker = state_change(&ev).squad_marker().unwrap();
+        assert_eq!(marker.position, [10.0, 20.0, 30.0]);
+        assert_eq!(marker.index, 2);
+    }
+}
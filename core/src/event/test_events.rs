@@ -0,0 +1,121 @@
+//! Events and agents for the unit tests. The instance ids of events equal the
+//! agent ids.
+use crate::{
+    Agent, BuffRemove, CombatEvent, CombatEventBuilder, CombatEventKind, StateChange,
+    StateChangeEvent, StrikeResult,
+};
+
+/// Builds the events, e.g. to add them to a tracker.
+pub(crate) fn events(events: &[CombatEventBuilder]) -> impl Iterator<Item = CombatEvent> + '_ {
+    events.iter().map(|ev| ev.build())
+}
+
+/// An event that only advances the time.
+pub(crate) fn at(time: u64) -> CombatEventBuilder {
+    CombatEventBuilder::new().time(time)
+}
+
+/// Direct damage of `src` to `dst`.
+pub(crate) fn strike(
+    time: u64,
+    src: usize,
+    dst: usize,
+    value: i32,
+    result: StrikeResult,
+) -> CombatEventBuilder {
+    at(time)
+        .src_agent(src)
+        .src_instance_id(src as u16)
+        .dst_agent(dst)
+        .dst_instance_id(dst as u16)
+        .value(value)
+        .result(result)
+}
+
+/// A tick of buff `buff_id` of `src` on `dst`. Negative damage heals.
+pub(crate) fn buff_damage(
+    time: u64,
+    src: usize,
+    dst: usize,
+    buff_id: u32,
+    damage: i32,
+) -> CombatEventBuilder {
+    at(time)
+        .src_agent(src)
+        .src_instance_id(src as u16)
+        .dst_agent(dst)
+        .dst_instance_id(dst as u16)
+        .skill_id(buff_id)
+        .buff(true)
+        .buff_dmg(damage)
+}
+
+/// Stack `stack_id` of buff `buff_id` applied by `src` to `dst`.
+pub(crate) fn buff_apply(
+    time: u64,
+    src: usize,
+    dst: usize,
+    buff_id: u32,
+    stack_id: u32,
+    duration: i32,
+) -> CombatEventBuilder {
+    at(time)
+        .src_agent(src)
+        .dst_agent(dst)
+        .skill_id(buff_id)
+        .buff(true)
+        .value(duration)
+        .stack_id(stack_id)
+}
+
+/// Buff `buff_id` removed from `target`.
+pub(crate) fn buff_remove(
+    time: u64,
+    target: usize,
+    buff_id: u32,
+    kind: BuffRemove,
+    stack_id: u32,
+) -> CombatEventBuilder {
+    at(time)
+        .src_agent(target)
+        .skill_id(buff_id)
+        .buff(true)
+        .buff_remove(kind)
+        .stack_id(stack_id)
+}
+
+/// A state change about `src`.
+pub(crate) fn state(time: u64, state_change: StateChange, src: usize) -> CombatEventBuilder {
+    at(time).src_agent(src).state_change(state_change)
+}
+
+/// Returns the state change view of an event.
+pub(crate) fn state_change(ev: &CombatEvent) -> StateChangeEvent<'_> {
+    match ev.kind() {
+        CombatEventKind::StateChange(state_change) => state_change,
+        kind => panic!("not a state change: {kind:?}"),
+    }
+}
+
+pub(crate) fn npc(id: usize, species_id: u16) -> Agent<'static> {
+    Agent {
+        name: None,
+        id,
+        prof: species_id as u32,
+        elite: u32::MAX,
+        self_: 0,
+        team: 0,
+    }
+}
+
+/// The local player, a core warrior.
+pub(crate) fn player(id: usize) -> Agent<'static> {
+    Agent {
+        name: None,
+        id,
+        prof: 2,
+        elite: 0,
+        self_: 1,
+        team: 0,
+    }
+}
@@ -0,0 +1,536 @@
+//! Parser for the `.evtc` and `.zevtc` log files written by arcdps.
+//!
+//! The events in a log use the same layout as the [CombatEvent]s that are
+//! passed to the combat callbacks, so tools working on logs and plugins can
+//! share their code. [Replayer] feeds a parsed log into the combat callbacks
+//! of a plugin.
+mod replay;
+
+use std::{
+    fmt::{Display, Formatter},
+    fs::File,
+    io::{BufReader, Read, Seek},
+    path::Path,
+};
+
+pub use replay::*;
+
+use crate::{AgentOwned, CombatEvent, CombatEventRev0};
+
+const AGENT_SIZE: usize = 96;
+const SKILL_SIZE: usize = 68;
+const EVENT_SIZE: usize = 64;
+const NAME_SIZE: usize = 64;
+
+/// State change of the event that marks the recording player.
+const STATECHANGE_POV: u8 = 13;
+
+/// The header of a log.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct EvtcHeader {
+    /// Build date of arcdps, e.g. `20250101`.
+    pub build_date: String,
+    /// Revision of the event layout in the file. The parsed events always
+    /// have the layout of revision 1.
+    pub revision: u8,
+    /// Species id of the boss that triggered the log, or the map id for
+    /// logs that were not triggered by a boss.
+    pub boss_id: u16,
+}
+
+/// An agent from the agent table of a log.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct EvtcAgent {
+    /// The agent as it would be passed to the combat callbacks.
+    /// [`AgentOwned::name`] holds the character name for players and
+    /// [`AgentOwned::self_`] is set for the player that recorded the log.
+    pub agent: AgentOwned,
+    /// Account name of a player, without leading ':'.
+    pub account_name: Option<String>,
+    /// Subgroup of a player.
+    pub subgroup: Option<u8>,
+    pub toughness: i16,
+    pub concentration: i16,
+    pub healing: i16,
+    pub condition: i16,
+    pub hitbox_width: i16,
+    pub hitbox_height: i16,
+}
+
+/// An entry of the skill table of a log.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct EvtcSkill {
+    pub id: u32,
+    pub name: String,
+}
+
+/// A parsed log.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Evtc {
+    pub header: EvtcHeader,
+    pub agents: Vec<EvtcAgent>,
+    pub skills: Vec<EvtcSkill>,
+    pub events: Vec<CombatEvent>,
+}
+
+/// Errors that can occur while parsing a log.
+#[derive(Debug)]
+pub enum EvtcError {
+    Io(std::io::Error),
+    Zip(zip::result::ZipError),
+    /// The file does not start with `EVTC`.
+    InvalidHeader,
+    /// The event layout of this revision is not supported.
+    UnsupportedRevision(u8),
+    /// The compressed log does not contain a file.
+    EmptyArchive,
+}
+
+impl Display for EvtcError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvtcError::Io(e) => write!(f, "failed to read log: {e}"),
+            EvtcError::Zip(e) => write!(f, "failed to decompress log: {e}"),
+            EvtcError::InvalidHeader => write!(f, "file is not an evtc log"),
+            EvtcError::UnsupportedRevision(rev) => write!(f, "unsupported evtc revision {rev}"),
+            EvtcError::EmptyArchive => write!(f, "compressed log is empty"),
+        }
+    }
+}
+
+impl std::error::Error for EvtcError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EvtcError::Io(e) => Some(e),
+            EvtcError::Zip(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for EvtcError {
+    fn from(e: std::io::Error) -> Self {
+        EvtcError::Io(e)
+    }
+}
+
+impl From<zip::result::ZipError> for EvtcError {
+    fn from(e: zip::result::ZipError) -> Self {
+        EvtcError::Zip(e)
+    }
+}
+
+impl Evtc {
+    /// Reads a `.evtc` or `.zevtc` file. The format is detected from the
+    /// content.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, EvtcError> {
+        let mut file = BufReader::new(File::open(path)?);
+        let mut magic = [0u8; 4];
+        file.read_exact(&mut magic)?;
+        file.rewind()?;
+
+        if &magic == b"PK\x03\x04" {
+            Self::parse_zevtc(file)
+        } else {
+            Self::parse(file)
+        }
+    }
+
+    /// Parses a compressed `.zevtc` log.
+    pub fn parse_zevtc(reader: impl Read + Seek) -> Result<Self, EvtcError> {
+        let mut archive = zip::ZipArchive::new(reader)?;
+        if archive.is_empty() {
+            return Err(EvtcError::EmptyArchive);
+        }
+        let file = archive.by_index(0)?;
+        Self::parse(BufReader::new(file))
+    }
+
+    /// Parses an uncompressed `.evtc` log.
+    /// ### Remarks
+    /// Events of revision 0 logs are converted to the layout of revision 1,
+    /// see [`CombatEvent::normalized`].
+    /// A log that was cut off in the middle of an event, for example because
+    /// the game crashed, is read up to the last complete event.
+    pub fn parse(mut reader: impl Read) -> Result<Self, EvtcError> {
+        let mut header = [0u8; 16];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != b"EVTC" {
+            return Err(EvtcError::InvalidHeader);
+        }
+        let header = EvtcHeader {
+            build_date: String::from_utf8_lossy(&header[4..12]).into_owned(),
+            revision: header[12],
+            boss_id: u16::from_le_bytes([header[13], header[14]]),
+        };
+        let parse_event = match header.revision {
+            0 => parse_event_rev0,
+            1 => parse_event,
+            revision => return Err(EvtcError::UnsupportedRevision(revision)),
+        };
+
+        // the counts are not trusted for preallocation, a broken log could
+        // request gigabytes
+        let agent_count = read_u32(&mut reader)?;
+        let mut agents = Vec::new();
+        let mut buf = [0u8; AGENT_SIZE];
+        for _ in 0..agent_count {
+            reader.read_exact(&mut buf)?;
+            agents.push(parse_agent(&buf));
+        }
+
+        let skill_count = read_u32(&mut reader)?;
+        let mut skills = Vec::new();
+        let mut buf = [0u8; SKILL_SIZE];
+        for _ in 0..skill_count {
+            reader.read_exact(&mut buf)?;
+            skills.push(EvtcSkill {
+                id: u32::from_le_bytes(buf[0..4].try_into().unwrap()),
+                name: read_name(&buf[4..4 + NAME_SIZE]),
+            });
+        }
+
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        let events: Vec<_> = data.chunks_exact(EVENT_SIZE).map(parse_event).collect();
+
+        if let Some(pov) = events
+            .iter()
+            .find(|ev| ev.is_statechange == STATECHANGE_POV)
+        {
+            for agent in agents.iter_mut().filter(|a| a.agent.id == pov.src_agent) {
+                agent.agent.self_ = 1;
+            }
+        }
+
+        Ok(Self {
+            header,
+            agents,
+            skills,
+            events,
+        })
+    }
+
+    /// Returns the agent with the given address.
+    pub fn agent(&self, id: usize) -> Option<&EvtcAgent> {
+        self.agents.iter().find(|agent| agent.agent.id == id)
+    }
+
+    /// Returns the name of the skill with the given id.
+    pub fn skill_name(&self, id: u32) -> Option<&str> {
+        self.skills
+            .iter()
+            .find(|skill| skill.id == id)
+            .map(|skill| skill.name.as_str())
+    }
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32, EvtcError> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+/// Reads a null terminated string from a fixed size buffer.
+fn read_name(buf: &[u8]) -> String {
+    let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    String::from_utf8_lossy(&buf[..end]).into_owned()
+}
+
+fn parse_agent(buf: &[u8; AGENT_SIZE]) -> EvtcAgent {
+    let i16_at = |i: usize| i16::from_le_bytes([buf[i], buf[i + 1]]);
+    let prof = u32::from_le_bytes(buf[8..12].try_into().unwrap());
+    let elite = u32::from_le_bytes(buf[12..16].try_into().unwrap());
+
+    // players are stored as "character\0:account\0subgroup\0"
+    let name = &buf[28..28 + NAME_SIZE];
+    let mut parts = name.split(|&b| b == 0);
+    let character = parts
+        .next()
+        .map(|p| String::from_utf8_lossy(p).into_owned());
+    let (account_name, subgroup) = if elite != u32::MAX {
+        let account = parts.next().filter(|p| !p.is_empty()).map(|p| {
+            String::from_utf8_lossy(p)
+                .trim_start_matches(':')
+                .to_string()
+        });
+        let subgroup = parts
+            .next()
+            .and_then(|p| std::str::from_utf8(p).ok())
+            .and_then(|p| p.parse().ok());
+        (account, subgroup)
+    } else {
+        (None, None)
+    };
+
+    EvtcAgent {
+        agent: AgentOwned {
+            name: character,
+            id: u64::from_le_bytes(buf[0..8].try_into().unwrap()) as usize,
+            prof,
+            elite,
+            self_: 0,
+            team: 0,
+        },
+        account_name,
+        subgroup,
+        toughness: i16_at(16),
+        concentration: i16_at(18),
+        healing: i16_at(20),
+        hitbox_width: i16_at(22),
+        condition: i16_at(24),
+        hitbox_height: i16_at(26),
+    }
+}
+
+/// Decodes an event of revision 1, which has the same layout as
+/// [CombatEvent].
+fn parse_event(buf: &[u8]) -> CombatEvent {
+    let u16_at = |i: usize| u16::from_le_bytes([buf[i], buf[i + 1]]);
+    let u32_at = |i: usize| u32::from_le_bytes(buf[i..i + 4].try_into().unwrap());
+    let u64_at = |i: usize| u64::from_le_bytes(buf[i..i + 8].try_into().unwrap());
+
+    CombatEvent {
+        time: u64_at(0),
+        src_agent: u64_at(8) as usize,
+        dst_agent: u64_at(16) as usize,
+        value: u32_at(24) as i32,
+        buff_dmg: u32_at(28) as i32,
+        overstack_value: u32_at(32),
+        skill_id: u32_at(36),
+        src_instance_id: u16_at(40),
+        dst_instance_id: u16_at(42),
+        src_master_instance_id: u16_at(44),
+        dst_master_instance_id: u16_at(46),
+        iff: buf[48],
+        buff: buf[49],
+        result: buf[50],
+        is_activation: buf[51],
+        is_buff_remove: buf[52],
+        is_ninety: buf[53],
+        is_fifty: buf[54],
+        is_moving: buf[55],
+        is_statechange: buf[56],
+        is_flanking: buf[57],
+        is_shields: buf[58],
+        is_off_cycle: buf[59],
+        pad61: buf[60],
+        pad62: buf[61],
+        pad63: buf[62],
+        pad64: buf[63],
+    }
+}
+
+/// Decodes an event of revision 0 and converts it to the layout of revision 1.
+fn parse_event_rev0(buf: &[u8]) -> CombatEvent {
+    let u16_at = |i: usize| u16::from_le_bytes([buf[i], buf[i + 1]]);
+    let u32_at = |i: usize| u32::from_le_bytes(buf[i..i + 4].try_into().unwrap());
+    let u64_at = |i: usize| u64::from_le_bytes(buf[i..i + 8].try_into().unwrap());
+
+    let ev = CombatEventRev0 {
+        time: u64_at(0),
+        src_agent: u64_at(8) as usize,
+        dst_agent: u64_at(16) as usize,
+        value: u32_at(24) as i32,
+        buff_dmg: u32_at(28) as i32,
+        overstack_value: u16_at(32),
+        skill_id: u16_at(34),
+        src_instance_id: u16_at(36),
+        dst_instance_id: u16_at(38),
+        src_master_instance_id: u16_at(40),
+        iss_offset: buf[42],
+        iss_offset_target: buf[43],
+        iss_bd_offset: buf[44],
+        iss_bd_offset_target: buf[45],
+        iss_alt_offset: buf[46],
+        iss_alt_offset_target: buf[47],
+        skar: buf[48],
+        skar_alt: buf[49],
+        skar_use_alt: buf[50],
+        iff: buf[51],
+        buff: buf[52],
+        result: buf[53],
+        is_activation: buf[54],
+        is_buff_remove: buf[55],
+        is_ninety: buf[56],
+        is_fifty: buf[57],
+        is_moving: buf[58],
+        is_statechange: buf[59],
+        is_flanking: buf[60],
+        is_shields: buf[61],
+        is_off_cycle: buf[62],
+        pad64: buf[63],
+    };
+    (&ev).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use super::*;
+    use crate::{CombatEventBuilder, StateChange};
+
+    const BOSS_ID: u16 = 15438;
+    pub(super) const PLAYER: usize = 0x1000;
+
+    /// Builds a log with one player, one skill and the given event records.
+    pub(super) fn log(revision: u8, events: &[[u8; EVENT_SIZE]]) -> Vec<u8> {
+        let mut data = b"EVTC20250101".to_vec();
+        data.push(revision);
+        data.extend_from_slice(&BOSS_ID.to_le_bytes());
+        data.push(0);
+
+        data.extend_from_slice(&1u32.to_le_bytes());
+        let mut agent = [0u8; AGENT_SIZE];
+        agent[0..8].copy_from_slice(&(PLAYER as u64).to_le_bytes());
+        agent[8..12].copy_from_slice(&1u32.to_le_bytes());
+        agent[12..16].copy_from_slice(&27u32.to_le_bytes());
+        agent[16..18].copy_from_slice(&10i16.to_le_bytes());
+        let name = b"Char Name\0:Account.1234\0\x33\0";
+        agent[28..28 + name.len()].copy_from_slice(name);
+        data.extend_from_slice(&agent);
+
+        data.extend_from_slice(&1u32.to_le_bytes());
+        let mut skill = [0u8; SKILL_SIZE];
+        skill[0..4].copy_from_slice(&5492u32.to_le_bytes());
+        skill[4..12].copy_from_slice(b"Fireball");
+        data.extend_from_slice(&skill);
+
+        for ev in events {
+            data.extend_from_slice(ev);
+        }
+        data
+    }
+
+    pub(super) fn rev1(ev: &CombatEvent) -> [u8; EVENT_SIZE] {
+        let mut buf = [0u8; EVENT_SIZE];
+        buf[0..8].copy_from_slice(&ev.time.to_le_bytes());
+        buf[8..16].copy_from_slice(&(ev.src_agent as u64).to_le_bytes());
+        buf[16..24].copy_from_slice(&(ev.dst_agent as u64).to_le_bytes());
+        buf[24..28].copy_from_slice(&ev.value.to_le_bytes());
+        buf[28..32].copy_from_slice(&ev.buff_dmg.to_le_bytes());
+        buf[32..36].copy_from_slice(&ev.overstack_value.to_le_bytes());
+        buf[36..40].copy_from_slice(&ev.skill_id.to_le_bytes());
+        buf[40..42].copy_from_slice(&ev.src_instance_id.to_le_bytes());
+        buf[42..44].copy_from_slice(&ev.dst_instance_id.to_le_bytes());
+        buf[50] = ev.result;
+        buf[56] = ev.is_statechange;
+        buf[57] = ev.is_flanking;
+        buf
+    }
+
+    pub(super) fn strike() -> CombatEvent {
+        CombatEventBuilder::new()
+            .time(100)
+            .src_agent(PLAYER)
+            .dst_agent(0x2000)
+            .value(1234)
+            .skill_id(5492)
+            .src_instance_id(7)
+            .is_flanking(true)
+            .build()
+    }
+
+    pub(super) fn pov() -> CombatEvent {
+        CombatEventBuilder::new()
+            .time(50)
+            .src_agent(PLAYER)
+            .state_change(StateChange::PointOfView)
+            .build()
+    }
+
+    fn assert_log(evtc: &Evtc, revision: u8) {
+        assert_eq!(evtc.header.build_date, "20250101");
+        assert_eq!(evtc.header.revision, revision);
+        assert_eq!(evtc.header.boss_id, BOSS_ID);
+
+        let player = evtc.agent(PLAYER).unwrap();
+        assert_eq!(player.agent.name.as_deref(), Some("Char Name"));
+        assert_eq!(player.account_name.as_deref(), Some("Account.1234"));
+        assert_eq!(player.subgroup, Some(3));
+        assert_eq!(player.agent.prof, 1);
+        assert_eq!(player.agent.elite, 27);
+        assert_eq!(player.toughness, 10);
+        assert_eq!(player.agent.self_, 1);
+        assert_eq!(evtc.skill_name(5492), Some("Fireball"));
+
+        assert_eq!(evtc.events.len(), 2);
+        let ev = &evtc.events[1];
+        assert_eq!(ev.time, 100);
+        assert_eq!(ev.src_agent, PLAYER);
+        assert_eq!(ev.dst_agent, 0x2000);
+        assert_eq!(ev.value, 1234);
+        assert_eq!(ev.skill_id, 5492);
+        assert_eq!(ev.src_instance_id, 7);
+        assert_eq!(ev.is_flanking, 1);
+    }
+
+    #[test]
+    fn parses_rev1() {
+        let mut data = log(1, &[rev1(&pov()), rev1(&strike())]);
+        // a cut off event is ignored
+        data.extend_from_slice(&[0; 20]);
+        let evtc = Evtc::parse(data.as_slice()).unwrap();
+        assert_log(&evtc, 1);
+    }
+
+    #[test]
+    fn parses_rev0() {
+        let rev0 = |ev: &CombatEvent| {
+            let mut buf = [0u8; EVENT_SIZE];
+            buf[0..8].copy_from_slice(&ev.time.to_le_bytes());
+            buf[8..16].copy_from_slice(&(ev.src_agent as u64).to_le_bytes());
+            buf[16..24].copy_from_slice(&(ev.dst_agent as u64).to_le_bytes());
+            buf[24..28].copy_from_slice(&ev.value.to_le_bytes());
+            buf[34..36].copy_from_slice(&(ev.skill_id as u16).to_le_bytes());
+            buf[36..38].copy_from_slice(&ev.src_instance_id.to_le_bytes());
+            buf[59] = ev.is_statechange;
+            buf[60] = ev.is_flanking;
+            buf
+        };
+        let data = log(0, &[rev0(&pov()), rev0(&strike())]);
+        let evtc = Evtc::parse(data.as_slice()).unwrap();
+        assert_log(&evtc, 0);
+    }
+
+    #[test]
+    fn parses_zevtc() {
+        let data = log(1, &[rev1(&pov()), rev1(&strike())]);
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file("log.evtc", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(&data).unwrap();
+        let archive = zip.finish().unwrap().into_inner();
+
+        let evtc = Evtc::parse_zevtc(Cursor::new(archive)).unwrap();
+        assert_log(&evtc, 1);
+    }
+
+    #[test]
+    fn rejects_invalid_logs() {
+        assert!(matches!(
+            Evtc::parse(&b"NOPE20250101\x01\0\0\0"[..]),
+            Err(EvtcError::InvalidHeader)
+        ));
+
+        let mut data = log(2, &[]);
+        assert!(matches!(
+            Evtc::parse(data.as_slice()),
+            Err(EvtcError::UnsupportedRevision(2))
+        ));
+
+        // a huge agent count with no agents must fail instead of allocating
+        data = log(1, &[]);
+        data.truncate(16);
+        data.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            Evtc::parse(data.as_slice()),
+            Err(EvtcError::Io(_))
+        ));
+    }
+}
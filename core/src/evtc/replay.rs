@@ -0,0 +1,341 @@
+use std::{
+    collections::HashMap,
+    ffi::CString,
+    ptr::null_mut,
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::Evtc;
+use crate::{helpers, RawAgent, RawCombatCallback};
+
+/// How fast a [Replayer] passes the events to the callbacks.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ReplaySpeed {
+    /// Waits between the events as long as they were apart in the fight.
+    RealTime,
+    /// Like [`ReplaySpeed::RealTime`], but the fight runs the given factor
+    /// faster.
+    Accelerated(f64),
+    /// Passes the events without waiting.
+    Unthrottled,
+}
+
+/// Feeds the events of a parsed log into combat callbacks, in the same way
+/// arcdps would have passed them during the fight.
+///
+/// Every player of the log is announced with an agent added notification
+/// before the first event and removed with an agent removed notification
+/// after the last one.
+///
+/// ### Remarks
+/// A log does not tell which events arcdps passed to the local callback. The
+/// local callback gets the agent notifications and the events the recording
+/// player is the source or destination of, the area callback gets everything.
+/// Skill names are kept alive for the lifetime of the process, like arcdps
+/// does.
+///
+/// ```no_run
+/// use arcdps::{
+///     evtc::{Evtc, EvtcError, ReplaySpeed, Replayer},
+///     ArcDpsExport,
+/// };
+///
+/// fn replay(export: &ArcDpsExport) -> Result<(), EvtcError> {
+///     let log = Evtc::from_file("20250101-200000.zevtc")?;
+///     unsafe {
+///         Replayer::new(&log)
+///             .speed(ReplaySpeed::Unthrottled)
+///             .replay(export.combat, export.combat_local)
+///     };
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Replayer<'a> {
+    evtc: &'a Evtc,
+    speed: ReplaySpeed,
+}
+
+impl<'a> Replayer<'a> {
+    /// Creates a replayer that passes the events in real time.
+    pub fn new(evtc: &'a Evtc) -> Self {
+        Self {
+            evtc,
+            speed: ReplaySpeed::RealTime,
+        }
+    }
+
+    /// Sets the speed of the replay.
+    pub fn speed(mut self, speed: ReplaySpeed) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Passes all events of the log to the callbacks and returns once the last
+    /// event was passed.
+    ///
+    /// # Safety
+    /// The callbacks must be safe to call with valid arguments, e.g. the
+    /// callbacks from the [ArcDpsExport](crate::ArcDpsExport) of a loaded
+    /// plugin.
+    pub unsafe fn replay(
+        &self,
+        combat: Option<RawCombatCallback>,
+        combat_local: Option<RawCombatCallback>,
+    ) {
+        let emit = |local: bool,
+                    ev: Option<&_>,
+                    src: Option<&RawAgent>,
+                    dst: Option<&RawAgent>,
+                    skill,
+                    id| {
+            let local = combat_local.filter(|_| local);
+            for callback in combat.iter().chain(local.iter()) {
+                // the parsed events always have the layout of revision 1
+                callback(ev, src, dst, skill, id, 1);
+            }
+        };
+
+        let players = self.players();
+        let mut id = 0;
+        for player in &players {
+            id += 1;
+            emit(
+                true,
+                None,
+                Some(&player.src),
+                Some(&player.dst),
+                null_mut(),
+                id,
+            );
+        }
+        let pov = self
+            .evtc
+            .agents
+            .iter()
+            .find(|agent| agent.agent.self_ != 0)
+            .map(|agent| agent.agent.id);
+
+        let skills: HashMap<_, _> = self
+            .evtc
+            .skills
+            .iter()
+            .map(|skill| (skill.id, helpers::intern_skill_name(&skill.name)))
+            .collect();
+        let names: HashMap<_, _> = self
+            .evtc
+            .agents
+            .iter()
+            .map(|agent| {
+                let name = agent.agent.name.as_deref().map(to_cstring);
+                (agent.agent.id, (agent, name))
+            })
+            .collect();
+        let agent = |id: usize| {
+            names.get(&id).map(|(agent, name)| RawAgent {
+                name: name.as_ref().map_or(null_mut(), |name| name.as_ptr() as _),
+                id: agent.agent.id,
+                prof: agent.agent.prof,
+                elite: agent.agent.elite,
+                self_: agent.agent.self_,
+                team: agent.agent.team,
+            })
+        };
+
+        let start = Instant::now();
+        let first_time = self.evtc.events.first().map_or(0, |ev| ev.time);
+        for ev in &self.evtc.events {
+            self.wait(start, ev.time.saturating_sub(first_time));
+
+            let src = agent(ev.src_agent);
+            let dst = agent(ev.dst_agent);
+            let skill = skills
+                .get(&ev.skill_id)
+                .map_or(null_mut(), |name| name.as_ptr() as _);
+            let local = pov.is_some_and(|pov| ev.src_agent == pov || ev.dst_agent == pov);
+            id += 1;
+            emit(local, Some(ev), src.as_ref(), dst.as_ref(), skill, id);
+        }
+
+        for player in &players {
+            let mut src = player.src;
+            src.prof = 0;
+            id += 1;
+            emit(true, None, Some(&src), Some(&player.dst), null_mut(), id);
+        }
+    }
+
+    /// Waits until `elapsed` milliseconds of the fight have passed.
+    fn wait(&self, start: Instant, elapsed: u64) {
+        let factor = match self.speed {
+            ReplaySpeed::RealTime => 1.0,
+            ReplaySpeed::Accelerated(factor) if factor > 0.0 => factor,
+            _ => return,
+        };
+        let target = Duration::from_secs_f64(elapsed as f64 / 1000.0 / factor);
+        if let Some(remaining) = target.checked_sub(start.elapsed()) {
+            thread::sleep(remaining);
+        }
+    }
+
+    /// Builds the agent added notifications for all players of the log.
+    fn players(&self) -> Vec<Notification> {
+        self.evtc
+            .agents
+            .iter()
+            .filter(|agent| agent.agent.elite != u32::MAX)
+            .map(|agent| {
+                let instance_id = self
+                    .evtc
+                    .events
+                    .iter()
+                    .find(|ev| ev.src_agent == agent.agent.id && ev.src_instance_id != 0)
+                    .map_or(0, |ev| ev.src_instance_id);
+                let character = to_cstring(agent.agent.name.as_deref().unwrap_or_default());
+                let account = to_cstring(&format!(
+                    ":{}",
+                    agent.account_name.as_deref().unwrap_or_default()
+                ));
+                Notification {
+                    src: RawAgent {
+                        name: character.as_ptr() as _,
+                        id: agent.agent.id,
+                        prof: agent.agent.prof,
+                        elite: 0,
+                        self_: 0,
+                        team: agent.agent.team,
+                    },
+                    dst: RawAgent {
+                        name: account.as_ptr() as _,
+                        id: instance_id as usize,
+                        prof: agent.agent.prof,
+                        elite: agent.agent.elite,
+                        self_: agent.agent.self_,
+                        team: agent.subgroup.unwrap_or_default() as u16,
+                    },
+                    _names: [character, account],
+                }
+            })
+            .collect()
+    }
+}
+
+/// An agent notification. `src` and `dst` point into `_names`.
+struct Notification {
+    src: RawAgent,
+    dst: RawAgent,
+    _names: [CString; 2],
+}
+
+fn to_cstring(s: &str) -> CString {
+    CString::new(s.replace('\0', "")).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ffi::CStr, sync::Mutex};
+
+    use super::*;
+    use crate::{
+        evtc::tests::{log, pov, rev1, strike, PLAYER},
+        CombatEvent, CombatEventBuilder, PCCHAR,
+    };
+
+    static CALLS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    fn name(agent: &RawAgent) -> String {
+        // SAFETY: the replayer passes valid names or null
+        unsafe { helpers::get_str_from_pc_char(agent.name) }
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    fn record(
+        target: &str,
+        ev: Option<&CombatEvent>,
+        src: Option<&RawAgent>,
+        dst: Option<&RawAgent>,
+        skill_name: PCCHAR,
+        id: u64,
+    ) {
+        let call = match (ev, src, dst) {
+            (Some(ev), ..) => {
+                // SAFETY: the replayer passes interned names or null
+                let skill = (!skill_name.is_null())
+                    .then(|| unsafe { CStr::from_ptr(skill_name) }.to_string_lossy());
+                format!("{target} {id} event {} {skill:?}", ev.time)
+            }
+            (None, Some(src), Some(dst)) => format!(
+                "{target} {id} agent {} {} {} / {} {} {} {} {} {}",
+                name(src),
+                src.prof,
+                src.elite,
+                name(dst),
+                dst.id,
+                dst.prof,
+                dst.elite,
+                dst.self_,
+                dst.team
+            ),
+            _ => format!("{target} {id} unexpected"),
+        };
+        CALLS.lock().unwrap().push(call);
+    }
+
+    unsafe extern "C" fn area(
+        ev: Option<&CombatEvent>,
+        src: Option<&RawAgent>,
+        dst: Option<&RawAgent>,
+        skill_name: PCCHAR,
+        id: u64,
+        _revision: u64,
+    ) {
+        record("area", ev, src, dst, skill_name, id);
+    }
+
+    unsafe extern "C" fn local(
+        ev: Option<&CombatEvent>,
+        src: Option<&RawAgent>,
+        dst: Option<&RawAgent>,
+        skill_name: PCCHAR,
+        id: u64,
+        _revision: u64,
+    ) {
+        record("local", ev, src, dst, skill_name, id);
+    }
+
+    #[test]
+    fn replays_log() {
+        let other = CombatEventBuilder::new()
+            .time(150)
+            .src_agent(0x2000)
+            .dst_agent(0x3000)
+            .value(10)
+            .build();
+        let data = log(1, &[rev1(&pov()), rev1(&strike()), rev1(&other)]);
+        let evtc = Evtc::parse(data.as_slice()).unwrap();
+        assert_eq!(evtc.agent(PLAYER).unwrap().agent.self_, 1);
+
+        unsafe {
+            Replayer::new(&evtc)
+                .speed(ReplaySpeed::Unthrottled)
+                .replay(Some(area), Some(local))
+        };
+
+        assert_eq!(
+            *CALLS.lock().unwrap(),
+            [
+                "area 1 agent Char Name 1 0 / :Account.1234 7 1 27 1 3",
+                "local 1 agent Char Name 1 0 / :Account.1234 7 1 27 1 3",
+                "area 2 event 50 None",
+                "local 2 event 50 None",
+                "area 3 event 100 Some(\"Fireball\")",
+                "local 3 event 100 Some(\"Fireball\")",
+                "area 4 event 150 None",
+                "area 5 agent Char Name 0 0 / :Account.1234 7 1 27 1 3",
+                "local 5 agent Char Name 0 0 / :Account.1234 7 1 27 1 3",
+            ]
+        );
+    }
+}
@@ -1,5 +1,13 @@
 #![allow(clippy::missing_safety_doc)]
-use std::ffi::CStr;
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    ffi::{CStr, CString},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, PoisonError,
+    },
+};
 
 use chrono::DateTime;
 
@@ -20,6 +28,50 @@ pub unsafe fn get_combat_args_from_raw<'a>(
     }
 }
 
+static REPORTED_REVISION: AtomicBool = AtomicBool::new(false);
+
+/// Converts the event of a combat callback to the current layout. Returns
+/// [`None`] if the event has to be skipped, because its revision is not
+/// supported. The first skipped event is logged as an error.
+#[doc(hidden)]
+pub fn __normalize_combat_event<'a>(
+    #[cfg_attr(not(feature = "log"), allow(unused_variables))] callback: &str,
+    ev: Option<&'a CombatEvent>,
+    revision: u64,
+) -> Option<Option<Cow<'a, CombatEvent>>> {
+    let Some(ev) = ev else {
+        // notifications don't depend on the layout
+        return Some(None);
+    };
+    match ev.normalized(revision) {
+        Ok(ev) => Some(Some(ev)),
+        #[cfg_attr(not(feature = "log"), allow(unused_variables))]
+        Err(err) => {
+            if !REPORTED_REVISION.swap(true, Ordering::Relaxed) {
+                #[cfg(feature = "log")]
+                log::error!("{}: {}, skipping these events", callback, err);
+            }
+            None
+        }
+    }
+}
+
+static SKILL_NAMES: Mutex<Option<HashMap<String, &'static CStr>>> = Mutex::new(None);
+
+/// Returns a skill name that stays valid for the lifetime of the process,
+/// like the skill names arcdps passes to the combat callbacks. Nul bytes are
+/// removed.
+pub fn intern_skill_name(name: &str) -> &'static CStr {
+    let mut names = SKILL_NAMES.lock().unwrap_or_else(PoisonError::into_inner);
+    names
+        .get_or_insert_with(Default::default)
+        .entry(name.to_string())
+        .or_insert_with(|| {
+            let name = CString::new(name.replace('\0', "")).unwrap_or_default();
+            Box::leak(name.into_boxed_c_str())
+        })
+}
+
 /// A helper function to convert arcdps strings to [`&str`].
 /// ### Remarks
 /// The result is not necessarily static.
@@ -46,7 +98,7 @@ pub unsafe fn get_str_from_ptr_and_len(src: *const u8, len: u64) -> &'static str
 }
 
 /// A helper function to convert raw arguments to safe abstractions
-pub fn convert_extras_user(user: &RawUserInfo) -> UserInfo {
+pub fn convert_extras_user(user: &RawUserInfo) -> UserInfo<'_> {
     let name = unsafe { get_str_from_pc_char(user.account_name as _) };
     UserInfo {
         account_name: name.map(|n| n.trim_start_matches(':')),
@@ -57,7 +109,7 @@ pub fn convert_extras_user(user: &RawUserInfo) -> UserInfo {
     }
 }
 
-pub fn convert_extras_squad_chat_message(msg: &RawSquadMessageInfo) -> SquadMessageInfo {
+pub fn convert_extras_squad_chat_message(msg: &RawSquadMessageInfo) -> SquadMessageInfo<'_> {
     let timestamp = unsafe { get_str_from_ptr_and_len(msg.timestamp, msg.timestamp_length) };
     let timestamp = DateTime::parse_from_rfc3339(timestamp).unwrap().to_utc();
 
@@ -80,7 +132,7 @@ pub fn convert_extras_squad_chat_message(msg: &RawSquadMessageInfo) -> SquadMess
     }
 }
 
-pub fn convert_extras_npc_chat_message(msg: &RawNpcMessageInfo) -> NpcMessageInfo {
+pub fn convert_extras_npc_chat_message(msg: &RawNpcMessageInfo) -> NpcMessageInfo<'_> {
     let character_name =
         unsafe { get_str_from_ptr_and_len(msg.character_name, msg.character_name_length) };
     let message = unsafe { get_str_from_ptr_and_len(msg.message, msg.message_length) };
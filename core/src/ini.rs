@@ -0,0 +1,405 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::{arcdps_ini_path, IniPathError, Modifiers};
+
+/// Section of `arcdps.ini` that holds the hotkeys.
+const KEYS_SECTION: &str = "keys";
+
+/// Prefix of the sections imgui stores its windows in, e.g.
+/// `[Window][Options]`.
+const WINDOW_PREFIX: &str = "Window][";
+
+/// How often [IniWatcher::poll] checks the file.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// An error while reading `arcdps.ini`.
+#[derive(Debug)]
+pub enum ArcIniError {
+    /// The path of the ini is unknown.
+    Path(IniPathError),
+    /// The ini couldn't be read.
+    Io(std::io::Error),
+}
+
+impl Display for ArcIniError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArcIniError::Path(e) => write!(f, "failed to locate arcdps.ini: {e}"),
+            ArcIniError::Io(e) => write!(f, "failed to read arcdps.ini: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ArcIniError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ArcIniError::Path(e) => Some(e),
+            ArcIniError::Io(e) => Some(e),
+        }
+    }
+}
+
+impl From<IniPathError> for ArcIniError {
+    fn from(e: IniPathError) -> Self {
+        ArcIniError::Path(e)
+    }
+}
+
+impl From<std::io::Error> for ArcIniError {
+    fn from(e: std::io::Error) -> Self {
+        ArcIniError::Io(e)
+    }
+}
+
+/// The position and state of a window, as imgui stores it in a
+/// `[Window][<title>]` section.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct WindowSettings {
+    /// Top left corner in pixels.
+    pub pos: Option<[f32; 2]>,
+    pub size: Option<[f32; 2]>,
+    pub collapsed: bool,
+    /// Whether the window is shown, if the section stores it.
+    pub visible: Option<bool>,
+}
+
+impl WindowSettings {
+    /// Returns the top right corner, e.g. to dock a window next to this one.
+    pub fn top_right(&self) -> Option<[f32; 2]> {
+        let [x, y] = self.pos?;
+        let [width, _] = self.size?;
+        Some([x + width, y])
+    }
+
+    /// Returns the bottom left corner, e.g. to dock a window below this one.
+    pub fn bottom_left(&self) -> Option<[f32; 2]> {
+        let [x, y] = self.pos?;
+        let [_, height] = self.size?;
+        Some([x, y + height])
+    }
+}
+
+/// The settings arcdps keeps in `arcdps.ini`.
+///
+/// Only the windows and hotkeys are typed. Every other entry is kept as text
+/// and can be read with [`ArcIni::get`]. The file belongs to arcdps, so it is
+/// never written.
+///
+/// ```no_run
+/// use arcdps::ArcIni;
+///
+/// let ini = ArcIni::read()?;
+/// for name in ini.hotkey_conflicts(&[0x10, 0x12, 0x4B]) {
+///     println!("shift + alt + K is already used by arcdps for {name}");
+/// }
+/// # Ok::<(), arcdps::ArcIniError>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct ArcIni {
+    /// The imgui windows by title.
+    pub windows: BTreeMap<String, WindowSettings>,
+    /// The virtual key codes of the hotkeys by name, from the `[keys]`
+    /// section. Unbound hotkeys have no keys.
+    pub hotkeys: BTreeMap<String, Vec<u16>>,
+    sections: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl ArcIni {
+    /// Reads the ini at the path returned by `e0`.
+    pub fn read() -> Result<Self, ArcIniError> {
+        Self::read_from(arcdps_ini_path()?)
+    }
+
+    /// Reads an ini file.
+    pub fn read_from(path: impl AsRef<Path>) -> Result<Self, ArcIniError> {
+        let bytes = std::fs::read(path)?;
+        Ok(Self::parse(&String::from_utf8_lossy(&bytes)))
+    }
+
+    /// Parses the content of an ini. Lines that are not a section, an entry
+    /// or a comment are ignored.
+    pub fn parse(content: &str) -> Self {
+        let mut ini = Self::default();
+        let mut section = String::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = name.to_string();
+                ini.sections.entry(section.clone()).or_default();
+                continue;
+            }
+            if let Some((key, value)) = line.split_once('=') {
+                ini.sections
+                    .entry(section.clone())
+                    .or_default()
+                    .insert(key.trim().to_string(), value.trim().to_string());
+            }
+        }
+
+        for (name, entries) in &ini.sections {
+            if let Some(title) = name.strip_prefix(WINDOW_PREFIX) {
+                ini.windows.insert(title.to_string(), parse_window(entries));
+            }
+        }
+        if let Some(keys) = ini.sections.get(KEYS_SECTION) {
+            ini.hotkeys = keys
+                .iter()
+                .map(|(name, value)| (name.clone(), parse_keys(value)))
+                .collect();
+        }
+        ini
+    }
+
+    /// Returns the raw value of an entry. Entries before the first section
+    /// are in the section `""`.
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.sections.get(section)?.get(key).map(String::as_str)
+    }
+
+    /// Returns the names of the sections.
+    pub fn sections(&self) -> impl Iterator<Item = &str> {
+        self.sections.keys().map(String::as_str)
+    }
+
+    /// Returns the settings of a window by its title.
+    pub fn window(&self, title: &str) -> Option<&WindowSettings> {
+        self.windows.get(title)
+    }
+
+    /// Returns the titles of the windows that are shown.
+    pub fn visible_windows(&self) -> impl Iterator<Item = &str> {
+        self.windows
+            .iter()
+            .filter(|(_, window)| window.visible == Some(true))
+            .map(|(title, _)| title.as_str())
+    }
+
+    /// Returns the names of the hotkeys that use the same keys, in any order.
+    /// A hotkey also conflicts if it uses the keys together with the
+    /// [Modifiers] of arcdps.
+    pub fn hotkey_conflicts<'a>(&'a self, keys: &[u16]) -> impl Iterator<Item = &'a str> {
+        let keys = key_set(keys.iter().copied());
+        let modifiers = Modifiers::read();
+        self.hotkeys
+            .iter()
+            .filter(move |(_, bound)| {
+                let plain = key_set(bound.iter().copied());
+                let with_modifiers = key_set(
+                    bound
+                        .iter()
+                        .copied()
+                        .chain([modifiers.modifier1, modifiers.modifier2]),
+                );
+                !plain.is_empty() && (plain == keys || with_modifiers == keys)
+            })
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+/// Sorts the keys and removes duplicates and unbound keys.
+fn key_set(keys: impl Iterator<Item = u16>) -> Vec<u16> {
+    let mut keys: Vec<u16> = keys.filter(|&key| key != 0).collect();
+    keys.sort_unstable();
+    keys.dedup();
+    keys
+}
+
+fn parse_window(entries: &BTreeMap<String, String>) -> WindowSettings {
+    let get = |key: &str| {
+        entries
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    };
+    WindowSettings {
+        pos: get("Pos").and_then(parse_pair),
+        size: get("Size").and_then(parse_pair),
+        collapsed: get("Collapsed").and_then(parse_bool).unwrap_or(false),
+        visible: get("Visible").and_then(parse_bool),
+    }
+}
+
+fn parse_pair(value: &str) -> Option<[f32; 2]> {
+    let (x, y) = value.split_once(',')?;
+    Some([x.trim().parse().ok()?, y.trim().parse().ok()?])
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "1" | "true" => Some(true),
+        "0" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parses virtual key codes separated by `,`, `+` or spaces. `0` is no key.
+fn parse_keys(value: &str) -> Vec<u16> {
+    value
+        .split([',', '+', ' '])
+        .filter_map(|key| {
+            let key = key.trim();
+            match key.strip_prefix("0x").or_else(|| key.strip_prefix("0X")) {
+                Some(hex) => u16::from_str_radix(hex, 16).ok(),
+                None => key.parse().ok(),
+            }
+        })
+        .filter(|&key| key != 0)
+        .collect()
+}
+
+/// Keeps an [ArcIni] up to date with the file.
+///
+/// [`IniWatcher::poll`] is cheap enough to call every frame, e.g. from the
+/// [ImguiCallback](crate::ImguiCallback).
+///
+/// ```no_run
+/// use std::sync::Mutex;
+///
+/// use arcdps::IniWatcher;
+///
+/// static INI: Mutex<Option<IniWatcher>> = Mutex::new(None);
+///
+/// fn imgui_tick() {
+///     let mut ini = INI.lock().unwrap();
+///     if ini.is_none() {
+///         *ini = IniWatcher::new().ok();
+///     }
+///     if let Some(watcher) = ini.as_mut() {
+///         if let Ok(true) = watcher.poll() {
+///             let _dock = watcher.ini().window("Options").and_then(|w| w.top_right());
+///             // move our windows next to the options window
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct IniWatcher {
+    path: PathBuf,
+    ini: ArcIni,
+    /// Modification time and length of the file that was read last.
+    stamp: (Option<SystemTime>, u64),
+    last_poll: Instant,
+}
+
+impl IniWatcher {
+    /// Reads the ini at the path returned by `e0`.
+    pub fn new() -> Result<Self, ArcIniError> {
+        Self::with_path(arcdps_ini_path()?)
+    }
+
+    /// Reads and watches an ini file.
+    pub fn with_path(path: impl Into<PathBuf>) -> Result<Self, ArcIniError> {
+        let mut watcher = Self {
+            path: path.into(),
+            ini: ArcIni::default(),
+            stamp: (None, 0),
+            last_poll: Instant::now(),
+        };
+        watcher.reload()?;
+        Ok(watcher)
+    }
+
+    /// Reads the file again if it changed. The file is checked at most once
+    /// per second. Returns whether the settings were read again.
+    ///
+    /// If reading fails, the previous settings are kept.
+    pub fn poll(&mut self) -> Result<bool, ArcIniError> {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return Ok(false);
+        }
+        self.last_poll = Instant::now();
+        if self.stamp()? == self.stamp {
+            return Ok(false);
+        }
+        self.reload()?;
+        Ok(true)
+    }
+
+    /// Reads the file again, even if it didn't change.
+    pub fn reload(&mut self) -> Result<(), ArcIniError> {
+        let stamp = self.stamp()?;
+        self.ini = ArcIni::read_from(&self.path)?;
+        self.stamp = stamp;
+        Ok(())
+    }
+
+    pub fn ini(&self) -> &ArcIni {
+        &self.ini
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn stamp(&self) -> Result<(Option<SystemTime>, u64), ArcIniError> {
+        let metadata = std::fs::metadata(&self.path)?;
+        Ok((metadata.modified().ok(), metadata.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "\
+; written by arcdps
+[Window][Options]
+Pos=100,50
+Size=400,300
+Collapsed=0
+Visible=1
+
+[Window][Squad]
+Pos=10,20
+Collapsed=1
+
+[keys]
+global_toggle=0x10,0x12,0x48
+options=0X10 + 0x12 + 84
+log=0
+# unbound
+
+[session]
+build=1
+";
+
+    #[test]
+    fn parses_ini() {
+        let ini = ArcIni::parse(SAMPLE);
+
+        let options = ini.window("Options").unwrap();
+        assert_eq!(options.pos, Some([100.0, 50.0]));
+        assert_eq!(options.size, Some([400.0, 300.0]));
+        assert!(!options.collapsed);
+        assert_eq!(options.top_right(), Some([500.0, 50.0]));
+        assert_eq!(options.bottom_left(), Some([100.0, 350.0]));
+
+        let squad = ini.window("Squad").unwrap();
+        assert!(squad.collapsed);
+        assert_eq!(squad.visible, None);
+        assert_eq!(squad.top_right(), None);
+        assert_eq!(ini.visible_windows().collect::<Vec<_>>(), ["Options"]);
+
+        assert_eq!(ini.hotkeys["global_toggle"], [0x10, 0x12, 0x48]);
+        assert_eq!(ini.hotkeys["options"], [0x10, 0x12, 84]);
+        assert!(ini.hotkeys["log"].is_empty());
+        assert_eq!(ini.hotkeys.len(), 3);
+
+        assert_eq!(ini.get("session", "build"), Some("1"));
+        assert_eq!(ini.get("keys", "# unbound"), None);
+        assert_eq!(
+            ini.sections().collect::<Vec<_>>(),
+            ["Window][Options", "Window][Squad", "keys", "session"]
+        );
+    }
+}
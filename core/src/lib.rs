@@ -1,38 +1,70 @@
 //! # Macro usage
 //! To see which fields are supported, have a look at [SupportedFields]
 
+mod agent;
+mod buffs;
+mod channel;
+mod colors;
+mod config;
+mod damage;
+mod emit;
+mod encounter;
+mod event;
+#[cfg(feature = "evtc")]
+pub mod evtc;
 mod exported_functions;
 pub mod helpers;
+mod ini;
 #[cfg(feature = "log")]
 mod logging;
+mod master;
+mod message;
+mod panic;
+mod plugin;
 mod raw_structs;
+mod registry;
+mod runtime;
+mod settings;
 mod unofficial_extras;
 
+pub use agent::*;
 pub use arcdps_codegen::*;
+pub use buffs::*;
+pub use channel::*;
+pub use colors::*;
+pub use config::*;
+pub use damage::*;
+pub use emit::*;
+pub use encounter::*;
+pub use event::*;
 pub use exported_functions::*;
 #[cfg(feature = "imgui")]
 pub use imgui;
+pub use ini::*;
+pub use master::*;
+pub use message::*;
+pub use panic::*;
+pub use plugin::*;
 pub use raw_structs::*;
-pub use unofficial_extras::raw_structs::*;
-
-#[doc(hidden)]
-pub unsafe fn __init(arc_version: PCCHAR, arcdps: HANDLE, #[allow(unused)] name: &'static str) {
-    __set_handle(arcdps);
-    ARC_VERSION = helpers::get_str_from_pc_char(arc_version);
-    #[cfg(feature = "log")]
-    let _ = log::set_boxed_logger(Box::new(logging::ArcdpsLogger::new(name)))
-        .map(|()| log::set_max_level(log::LevelFilter::Trace));
-}
-
-static mut ARC_VERSION: Option<&'static str> = None;
-
-/// returns the loaded arcdps version
-pub fn arcdps_version() -> &'static str {
-    unsafe { ARC_VERSION.unwrap() }
-}
+pub use registry::*;
+pub use runtime::*;
+pub use settings::*;
+pub use unofficial_extras::{raw_structs::*, raw_structs_keybinds::*};
 
 /// This struct isn't used anywhere. It is a reference on what fields are
 /// currently supported by the [arcdps_export!] macro.
+///
+/// Instead of the callback fields, a type implementing [Plugin] can be passed
+/// as `plugin: crate::MyPlugin`. It is exclusive with `init`, `release` and
+/// all callback fields.
+///
+/// `combat_channel` and `combat_local_channel` take a static [CombatChannel]
+/// and are exclusive with the other fields for the same callback.
+///
+/// `plugin_message` receives the [PluginMessage]s other plugins send with
+/// [send_message]. They are decoded from the area combat and not passed to
+/// the other combat callbacks. It is exclusive with `raw_combat` and
+/// `raw_combat_local`.
 pub struct SupportedFields {
     pub name: &'static str,
     pub sig: u32,
@@ -49,15 +81,22 @@ pub struct SupportedFields {
     pub raw_unofficial_extras_squad_update: Option<RawSquadUpdateCallbackSignature>,
     pub raw_unofficial_extras_chat_message: Option<RawChatMessageCallbackSignature>,
     pub raw_unofficial_extras_chat_message2: Option<RawChatMessage2CallbackSignature>,
+    pub raw_unofficial_extras_language_changed: Option<RawLanguageChangedCallbackSignature>,
+    pub raw_unofficial_extras_keybind_changed: Option<RawKeyBindChangedCallbackSignature>,
     pub wnd_nofilter: Option<WndProcCallback>,
     pub combat: Option<CombatCallback>,
     pub imgui: Option<ImguiCallback>,
     pub options_end: Option<OptionsCallback>,
     pub combat_local: Option<CombatCallback>,
+    pub combat_channel: Option<&'static CombatChannel>,
+    pub combat_local_channel: Option<&'static CombatChannel>,
+    pub plugin_message: Option<PluginMessageCallback>,
     pub wnd_filter: Option<WndProcCallback>,
     pub options_windows: Option<OptionsWindowsCallback>,
     pub unofficial_extras_init: Option<ExtrasInitFunc>,
     pub unofficial_extras_squad_update: Option<ExtrasSquadUpdateCallback>,
     pub unofficial_extras_chat_message: Option<ExtrasChatMessageCallback>,
     pub unofficial_extras_chat_message2: Option<ExtrasChatMessage2Callback>,
+    pub unofficial_extras_language_changed: Option<ExtrasLanguageChangedCallback>,
+    pub unofficial_extras_keybind_changed: Option<ExtrasKeyBindChangedCallback>,
 }
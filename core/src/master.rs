@@ -0,0 +1,221 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{AgentRegistry, CombatEvent, StateChange};
+
+/// Attributes minions like pets, clones, turrets and spirits to the agent that
+/// owns them, via `src_master_instance_id` and `dst_master_instance_id`.
+///
+/// The resolver keeps a table of the instance ids of all agents that appear
+/// in the events it processes. Instance ids get reused after an agent
+/// despawns, so masters are resolved at the time of the event and the table
+/// forgets agents on [`StateChange::Despawn`]. A minion whose master was not
+/// seen yet gets the first agent that shows up with the instance id, unless
+/// the minion despawned before.
+///
+/// ```
+/// use arcdps::{CombatEvent, MasterResolver};
+///
+/// fn damage_by_owner(resolver: &mut MasterResolver, ev: &CombatEvent) -> (usize, i32) {
+///     resolver.process(ev);
+///     (resolver.owner(ev.src_agent), ev.value)
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MasterResolver {
+    instance_ids: HashMap<u16, usize>,
+    /// The reverse of `instance_ids`.
+    agents: HashMap<usize, u16>,
+    masters: HashMap<usize, usize>,
+    /// Minions by the instance id of their master, which was not seen yet.
+    pending: HashMap<u16, HashSet<usize>>,
+    /// The reverse of `pending`.
+    waiting: HashMap<usize, u16>,
+}
+
+impl MasterResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the instance ids of the players in the registry. Useful for
+    /// minions whose master did not appear in an event yet.
+    pub fn add_players(&mut self, registry: &AgentRegistry) {
+        for player in registry.iter() {
+            self.add_instance_id(player.instance_id, player.id);
+        }
+    }
+
+    /// Adds or replaces the agent with the given instance id.
+    pub fn add_instance_id(&mut self, instance_id: u16, agent: usize) {
+        if instance_id == 0 || agent == 0 {
+            return;
+        }
+        match self.instance_ids.insert(instance_id, agent) {
+            Some(previous) if previous == agent => return,
+            Some(previous) => {
+                self.agents.remove(&previous);
+            }
+            None => {}
+        }
+        if let Some(previous) = self.agents.insert(agent, instance_id) {
+            if previous != instance_id {
+                self.instance_ids.remove(&previous);
+            }
+        }
+
+        for minion in self.pending.remove(&instance_id).unwrap_or_default() {
+            self.waiting.remove(&minion);
+            self.masters.insert(minion, agent);
+        }
+    }
+
+    /// Updates the tables with an event. Must be called for every event in
+    /// order.
+    pub fn process(&mut self, ev: &CombatEvent) {
+        if ev.is_statechange == StateChange::Despawn as u8 {
+            if let Some(instance_id) = self.agents.remove(&ev.src_agent) {
+                self.instance_ids.remove(&instance_id);
+            }
+            self.stop_waiting(ev.src_agent);
+            return;
+        }
+
+        self.add_instance_id(ev.src_instance_id, ev.src_agent);
+        self.add_instance_id(ev.dst_instance_id, ev.dst_agent);
+        self.add_master(ev.src_agent, ev.src_master_instance_id);
+        self.add_master(ev.dst_agent, ev.dst_master_instance_id);
+    }
+
+    fn add_master(&mut self, minion: usize, master_instance_id: u16) {
+        if minion == 0 || master_instance_id == 0 {
+            return;
+        }
+        self.stop_waiting(minion);
+        match self.instance_ids.get(&master_instance_id) {
+            Some(&agent) => {
+                self.masters.insert(minion, agent);
+            }
+            // the master despawned, the minion keeps it
+            None if self.masters.contains_key(&minion) => {}
+            None => {
+                self.pending
+                    .entry(master_instance_id)
+                    .or_default()
+                    .insert(minion);
+                self.waiting.insert(minion, master_instance_id);
+            }
+        }
+    }
+
+    fn stop_waiting(&mut self, minion: usize) {
+        let Some(instance_id) = self.waiting.remove(&minion) else {
+            return;
+        };
+        if let Some(minions) = self.pending.get_mut(&instance_id) {
+            minions.remove(&minion);
+            if minions.is_empty() {
+                self.pending.remove(&instance_id);
+            }
+        }
+    }
+
+    /// Returns the agent id of the direct master of a minion.
+    pub fn master(&self, minion: usize) -> Option<usize> {
+        self.masters.get(&minion).copied()
+    }
+
+    /// Returns the agent that owns the given agent, following masters of
+    /// masters, e.g. for clones that spawn clones. Returns the agent itself if
+    /// it has no master.
+    pub fn owner(&self, agent: usize) -> usize {
+        let mut owner = agent;
+        // bounded in case of cycles in broken logs
+        for _ in 0..8 {
+            match self.master(owner) {
+                Some(master) if master != owner => owner = master,
+                _ => break,
+            }
+        }
+        owner
+    }
+
+    /// Removes all agents, e.g. when a new fight starts.
+    pub fn clear(&mut self) {
+        self.instance_ids.clear();
+        self.agents.clear();
+        self.masters.clear();
+        self.pending.clear();
+        self.waiting.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{event::test_events::*, CombatEventBuilder, StrikeResult};
+
+    const MASTER: usize = 10;
+    const PET: usize = 20;
+
+    /// A strike of `minion`, whose master has the given instance id.
+    fn minion(time: u64, minion: usize, master_instance_id: u16) -> CombatEventBuilder {
+        strike(time, minion, 1, 10, StrikeResult::Normal).src_master_instance_id(master_instance_id)
+    }
+
+    fn resolver(evs: &[CombatEventBuilder]) -> MasterResolver {
+        let mut resolver = MasterResolver::new();
+        events(evs).for_each(|ev| resolver.process(&ev));
+        resolver
+    }
+
+    #[test]
+    fn pet_before_master() {
+        let mut resolver = resolver(&[minion(1, PET, MASTER as u16)]);
+        assert_eq!(resolver.master(PET), None);
+        assert_eq!(resolver.owner(PET), PET);
+
+        resolver.process(&strike(2, MASTER, 1, 10, StrikeResult::Normal).build());
+        assert_eq!(resolver.master(PET), Some(MASTER));
+        assert_eq!(resolver.owner(PET), MASTER);
+    }
+
+    #[test]
+    fn instance_id_reuse() {
+        const OTHER: usize = 30;
+        const OTHER_PET: usize = 40;
+        const ORPHAN: usize = 50;
+
+        let resolver = resolver(&[
+            strike(1, MASTER, 1, 10, StrikeResult::Normal),
+            minion(2, PET, MASTER as u16),
+            minion(3, ORPHAN, 11),
+            state(4, StateChange::Despawn, MASTER),
+            state(5, StateChange::Despawn, ORPHAN),
+            // the pet outlives its master
+            minion(5, PET, MASTER as u16),
+            // the instance ids of the master and the orphan's master get reused
+            at(6).src_agent(OTHER).src_instance_id(MASTER as u16),
+            at(7).src_agent(60).src_instance_id(11),
+            minion(8, OTHER_PET, MASTER as u16),
+        ]);
+
+        assert_eq!(resolver.master(PET), Some(MASTER));
+        assert_eq!(resolver.master(OTHER_PET), Some(OTHER));
+        assert_eq!(resolver.master(ORPHAN), None);
+    }
+
+    #[test]
+    fn chained_owner() {
+        const CLONE: usize = 30;
+
+        let resolver = resolver(&[
+            strike(1, MASTER, 1, 10, StrikeResult::Normal),
+            minion(2, PET, MASTER as u16),
+            minion(3, CLONE, PET as u16),
+        ]);
+
+        assert_eq!(resolver.master(CLONE), Some(PET));
+        assert_eq!(resolver.owner(CLONE), MASTER);
+        assert_eq!(resolver.owner(MASTER), MASTER);
+    }
+}
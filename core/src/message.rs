@@ -0,0 +1,469 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{Display, Formatter},
+    mem::size_of,
+    sync::{
+        atomic::{AtomicU16, Ordering},
+        Mutex, PoisonError,
+    },
+};
+
+use crate::{emit_area, CombatEvent, CombatEventBuilder, StateChange};
+
+/// `skill_id` of the events that carry a message.
+const MAGIC: u32 = u32::from_le_bytes(*b"ARCM");
+
+/// Payload bytes per event: `src_agent`, `dst_agent`, `buff_dmg` and the 11
+/// flag bytes besides `is_statechange`.
+pub const MESSAGE_CHUNK_SIZE: usize = 2 * size_of::<usize>() + 4 + 11;
+
+/// The longest payload a message can have.
+pub const MAX_MESSAGE_LEN: usize = u16::MAX as usize * MESSAGE_CHUNK_SIZE;
+
+/// Incomplete messages that are kept per [MessageDecoder]. The oldest one is
+/// dropped when another one starts.
+const MAX_PENDING: usize = 64;
+
+/// A message from another plugin, or from this plugin itself. See
+/// [`send_message`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct PluginMessage {
+    /// The `sig` of the sending plugin.
+    pub sender_sig: u32,
+    /// Id of the protocol, agreed on by sender and receiver.
+    pub plugin_id: u32,
+    /// Id of the message within the protocol.
+    pub message_id: u16,
+    pub payload: Vec<u8>,
+}
+
+/// Receives the messages of other plugins, see the `plugin_message` field of
+/// [arcdps_export!](crate::arcdps_export).
+pub type PluginMessageCallback = fn(message: &PluginMessage);
+
+impl PluginMessage {
+    /// Decodes the payload if the message has the ids of `T`.
+    pub fn decode<T: MessageType>(&self) -> Option<T> {
+        if self.plugin_id != T::PLUGIN_ID || self.message_id != T::MESSAGE_ID {
+            return None;
+        }
+        T::from_bytes(&self.payload)
+    }
+}
+
+/// A typed message that can be sent with [`send`] and decoded with
+/// [`PluginMessage::decode`].
+///
+/// ```no_run
+/// use arcdps::{MessageType, PluginMessage};
+///
+/// struct Ping(u32);
+///
+/// impl MessageType for Ping {
+///     const MESSAGE_ID: u16 = 1;
+///     const PLUGIN_ID: u32 = 0x1234;
+///
+///     fn to_bytes(&self) -> Vec<u8> {
+///         self.0.to_le_bytes().to_vec()
+///     }
+///
+///     fn from_bytes(bytes: &[u8]) -> Option<Self> {
+///         Some(Ping(u32::from_le_bytes(bytes.try_into().ok()?)))
+///     }
+/// }
+///
+/// fn plugin_message(message: &PluginMessage) {
+///     if let Some(Ping(n)) = message.decode::<Ping>() {
+///         let _ = arcdps::send(&Ping(n + 1));
+///     }
+/// }
+/// ```
+pub trait MessageType: Sized {
+    const PLUGIN_ID: u32;
+    const MESSAGE_ID: u16;
+
+    fn to_bytes(&self) -> Vec<u8>;
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+/// An error while sending a [PluginMessage].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SendError {
+    /// The payload is longer than [MAX_MESSAGE_LEN].
+    TooLarge(usize),
+    /// The plugin is not loaded or arcdps doesn't provide `e9`.
+    NotLoaded,
+}
+
+impl Display for SendError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SendError::TooLarge(len) => {
+                write!(f, "message of {len} bytes exceeds {MAX_MESSAGE_LEN} bytes")
+            }
+            SendError::NotLoaded => write!(f, "the plugin is not loaded"),
+        }
+    }
+}
+
+impl std::error::Error for SendError {}
+
+static SEQUENCE: AtomicU16 = AtomicU16::new(0);
+
+/// Sends a message to all plugins with [emit_area]. Large payloads are split
+/// over several events.
+///
+/// ### Remarks
+/// arcdps also writes the events into its logs, so don't send secrets and
+/// keep messages rare. The sending plugin receives its own messages as well.
+pub fn send_message(plugin_id: u32, message_id: u16, payload: &[u8]) -> Result<(), SendError> {
+    if payload.len() > MAX_MESSAGE_LEN {
+        return Err(SendError::TooLarge(payload.len()));
+    }
+    let sequence = SEQUENCE.fetch_add(1, Ordering::Relaxed);
+    for ev in encode_message(plugin_id, message_id, sequence, payload) {
+        if !emit_area(&ev) {
+            return Err(SendError::NotLoaded);
+        }
+    }
+    Ok(())
+}
+
+/// Sends a typed message, see [`send_message`].
+pub fn send<T: MessageType>(message: &T) -> Result<(), SendError> {
+    send_message(T::PLUGIN_ID, T::MESSAGE_ID, &message.to_bytes())
+}
+
+/// Splits a message into the events that carry it. `sequence` tells messages
+/// of the same sender apart. The payload must not be longer than
+/// [MAX_MESSAGE_LEN].
+pub fn encode_message(
+    plugin_id: u32,
+    message_id: u16,
+    sequence: u16,
+    payload: &[u8],
+) -> impl Iterator<Item = CombatEvent> + '_ {
+    assert!(payload.len() <= MAX_MESSAGE_LEN, "the payload is too long");
+    let count = payload.len().div_ceil(MESSAGE_CHUNK_SIZE).max(1);
+    (0..count).map(move |index| {
+        let start = index * MESSAGE_CHUNK_SIZE;
+        let end = (start + MESSAGE_CHUNK_SIZE).min(payload.len());
+        let mut chunk = [0u8; MESSAGE_CHUNK_SIZE];
+        chunk[..end - start].copy_from_slice(&payload[start..end]);
+
+        let mut ev = CombatEventBuilder::new()
+            .state_change(StateChange::Extension)
+            .skill_id(MAGIC)
+            .overstack_value(plugin_id)
+            .src_instance_id(message_id)
+            .dst_instance_id(sequence)
+            .src_master_instance_id(index as u16)
+            .dst_master_instance_id(count as u16)
+            .value(payload.len() as i32)
+            .build();
+        write_chunk(&mut ev, &chunk);
+        ev
+    })
+}
+
+/// Whether the event carries a part of a [PluginMessage].
+pub fn is_message(ev: &CombatEvent) -> bool {
+    (ev.is_statechange == StateChange::Extension as u8
+        || ev.is_statechange == StateChange::ExtensionCombat as u8)
+        && ev.skill_id == MAGIC
+}
+
+const USIZE: usize = size_of::<usize>();
+
+fn write_chunk(ev: &mut CombatEvent, chunk: &[u8; MESSAGE_CHUNK_SIZE]) {
+    ev.src_agent = usize::from_le_bytes(chunk[..USIZE].try_into().unwrap());
+    ev.dst_agent = usize::from_le_bytes(chunk[USIZE..2 * USIZE].try_into().unwrap());
+    ev.buff_dmg = i32::from_le_bytes(chunk[2 * USIZE..2 * USIZE + 4].try_into().unwrap());
+    let [iff, buff, result, is_activation, is_buff_remove, is_ninety, is_fifty, is_moving, is_flanking, is_shields, is_off_cycle] =
+        chunk[2 * USIZE + 4..].try_into().unwrap();
+    ev.iff = iff;
+    ev.buff = buff;
+    ev.result = result;
+    ev.is_activation = is_activation;
+    ev.is_buff_remove = is_buff_remove;
+    ev.is_ninety = is_ninety;
+    ev.is_fifty = is_fifty;
+    ev.is_moving = is_moving;
+    ev.is_flanking = is_flanking;
+    ev.is_shields = is_shields;
+    ev.is_off_cycle = is_off_cycle;
+}
+
+fn read_chunk(ev: &CombatEvent) -> [u8; MESSAGE_CHUNK_SIZE] {
+    let mut chunk = [0u8; MESSAGE_CHUNK_SIZE];
+    chunk[..USIZE].copy_from_slice(&ev.src_agent.to_le_bytes());
+    chunk[USIZE..2 * USIZE].copy_from_slice(&ev.dst_agent.to_le_bytes());
+    chunk[2 * USIZE..2 * USIZE + 4].copy_from_slice(&ev.buff_dmg.to_le_bytes());
+    chunk[2 * USIZE + 4..].copy_from_slice(&[
+        ev.iff,
+        ev.buff,
+        ev.result,
+        ev.is_activation,
+        ev.is_buff_remove,
+        ev.is_ninety,
+        ev.is_fifty,
+        ev.is_moving,
+        ev.is_flanking,
+        ev.is_shields,
+        ev.is_off_cycle,
+    ]);
+    chunk
+}
+
+/// A message that is missing chunks.
+#[derive(Debug, Clone)]
+struct Pending {
+    message_id: u16,
+    len: usize,
+    count: usize,
+    /// The chunks that arrived, by index. Only grows with the chunks that
+    /// arrive, the count of a message comes from other plugins.
+    chunks: BTreeMap<u16, [u8; MESSAGE_CHUNK_SIZE]>,
+    /// Order in which the messages started, to drop the oldest one.
+    started: u64,
+}
+
+/// Reassembles [PluginMessage]s from the events that carry them.
+///
+/// The safe combat callback of [arcdps_export!](crate::arcdps_export) does
+/// this already if `plugin_message` is set. Chunks may arrive in any order,
+/// but a message is lost if one of its chunks is lost.
+#[derive(Debug, Clone, Default)]
+pub struct MessageDecoder {
+    /// Incomplete messages by sender sig, plugin id and sequence.
+    pending: HashMap<(u32, u32, u16), Pending>,
+    started: u64,
+}
+
+impl MessageDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an event. Returns the message once all of its chunks arrived.
+    /// Events that don't carry a message are ignored, see [`is_message`].
+    pub fn process(&mut self, ev: &CombatEvent) -> Option<PluginMessage> {
+        if !is_message(ev) {
+            return None;
+        }
+        let sender_sig = ev.pad_u32();
+        let plugin_id = ev.overstack_value;
+        let message_id = ev.src_instance_id;
+        let index = ev.src_master_instance_id;
+        let count = ev.dst_master_instance_id as usize;
+        let len = ev.value as u32 as usize;
+        if index as usize >= count || len > count * MESSAGE_CHUNK_SIZE {
+            return None;
+        }
+
+        let key = (sender_sig, plugin_id, ev.dst_instance_id);
+        if !self.pending.contains_key(&key) {
+            if self.pending.len() >= MAX_PENDING {
+                self.drop_oldest();
+            }
+            self.started += 1;
+        }
+        let started = self.started;
+        let new = || Pending {
+            message_id,
+            len,
+            count,
+            chunks: BTreeMap::new(),
+            started,
+        };
+        let pending = self.pending.entry(key).or_insert_with(new);
+        if pending.message_id != message_id || pending.count != count || pending.len != len {
+            // a new message reused the sequence, the old one is lost
+            *pending = new();
+        }
+
+        pending
+            .chunks
+            .entry(index)
+            .or_insert_with(|| read_chunk(ev));
+        if pending.chunks.len() < count {
+            return None;
+        }
+
+        let pending = self.pending.remove(&key)?;
+        let mut payload: Vec<u8> = pending.chunks.into_values().flatten().collect();
+        payload.truncate(pending.len);
+        Some(PluginMessage {
+            sender_sig,
+            plugin_id,
+            message_id,
+            payload,
+        })
+    }
+
+    fn drop_oldest(&mut self) {
+        let oldest = self
+            .pending
+            .iter()
+            .min_by_key(|(_, pending)| pending.started)
+            .map(|(&key, _)| key);
+        if let Some(key) = oldest {
+            self.pending.remove(&key);
+        }
+    }
+
+    /// Drops all incomplete messages.
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+}
+
+static DECODER: Mutex<Option<MessageDecoder>> = Mutex::new(None);
+
+/// Passes message events of a combat callback to the decoder. Returns true if
+/// the event carried a message and must not be passed to the combat callback.
+#[doc(hidden)]
+pub fn __route_message(ev: Option<&CombatEvent>, callback: PluginMessageCallback) -> bool {
+    let Some(ev) = ev.filter(|ev| is_message(ev)) else {
+        return false;
+    };
+    let message = DECODER
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get_or_insert_with(MessageDecoder::new)
+        .process(ev);
+    if let Some(message) = message {
+        callback(&message);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIG: u32 = 0xdead;
+    const PLUGIN_ID: u32 = 0x1234;
+
+    fn encode(message_id: u16, sequence: u16, payload: &[u8]) -> Vec<CombatEvent> {
+        encode_message(PLUGIN_ID, message_id, sequence, payload)
+            .map(|mut ev| {
+                // arcdps signs the events with the sig of the sender
+                [ev.pad61, ev.pad62, ev.pad63, ev.pad64] = SIG.to_le_bytes();
+                ev
+            })
+            .collect()
+    }
+
+    fn payload(len: usize) -> Vec<u8> {
+        (0..len).map(|i| i as u8).collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        for len in [
+            0,
+            1,
+            MESSAGE_CHUNK_SIZE,
+            3 * MESSAGE_CHUNK_SIZE,
+            3 * MESSAGE_CHUNK_SIZE + 5,
+        ] {
+            let payload = payload(len);
+            let events = encode(7, len as u16, &payload);
+            assert_eq!(events.len(), len.div_ceil(MESSAGE_CHUNK_SIZE).max(1));
+            assert!(events.iter().all(is_message));
+
+            let mut decoder = MessageDecoder::new();
+            let (last, rest) = events.split_last().unwrap();
+            for ev in rest {
+                assert_eq!(decoder.process(ev), None);
+            }
+            assert_eq!(
+                decoder.process(last),
+                Some(PluginMessage {
+                    sender_sig: SIG,
+                    plugin_id: PLUGIN_ID,
+                    message_id: 7,
+                    payload,
+                })
+            );
+            assert!(decoder.pending.is_empty());
+        }
+    }
+
+    #[test]
+    fn out_of_order() {
+        let payload = payload(4 * MESSAGE_CHUNK_SIZE);
+        let mut events = encode(1, 0, &payload);
+        events.swap(0, 3);
+        events.swap(1, 2);
+
+        let mut decoder = MessageDecoder::new();
+        let messages: Vec<_> = events.iter().filter_map(|ev| decoder.process(ev)).collect();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].payload, payload);
+    }
+
+    #[test]
+    fn duplicate_chunk() {
+        let payload = payload(2 * MESSAGE_CHUNK_SIZE);
+        let events = encode(1, 0, &payload);
+
+        let mut decoder = MessageDecoder::new();
+        assert_eq!(decoder.process(&events[0]), None);
+        assert_eq!(decoder.process(&events[0]), None);
+        assert_eq!(decoder.process(&events[1]).unwrap().payload, payload);
+    }
+
+    #[test]
+    fn reused_sequence() {
+        let old = encode(1, 5, &payload(2 * MESSAGE_CHUNK_SIZE));
+        let new = encode(2, 5, &payload(3));
+
+        let mut decoder = MessageDecoder::new();
+        assert_eq!(decoder.process(&old[0]), None);
+        let message = decoder.process(&new[0]).unwrap();
+        assert_eq!(message.message_id, 2);
+        assert_eq!(message.payload, payload(3));
+        // the old message is lost
+        assert_eq!(decoder.process(&old[1]), None);
+    }
+
+    #[test]
+    fn evicts_oldest() {
+        let messages: Vec<_> = (0..=MAX_PENDING as u16)
+            .map(|sequence| encode(1, sequence, &payload(2 * MESSAGE_CHUNK_SIZE)))
+            .collect();
+
+        let mut decoder = MessageDecoder::new();
+        for events in &messages {
+            assert_eq!(decoder.process(&events[0]), None);
+        }
+        assert_eq!(decoder.pending.len(), MAX_PENDING);
+        assert_eq!(decoder.process(&messages[0][1]), None);
+        assert!(decoder.process(&messages[MAX_PENDING][1]).is_some());
+        assert!(decoder.process(&messages[2][1]).is_some());
+    }
+
+    #[test]
+    fn ignores_invalid_chunks() {
+        let mut decoder = MessageDecoder::new();
+        let mut ev = encode(1, 0, &payload(3))[0];
+
+        // a huge count only keeps the chunks that arrived
+        ev.dst_master_instance_id = u16::MAX;
+        assert_eq!(decoder.process(&ev), None);
+        assert_eq!(decoder.pending.values().next().unwrap().chunks.len(), 1);
+
+        ev.src_master_instance_id = u16::MAX;
+        assert_eq!(decoder.process(&ev), None);
+        ev.src_master_instance_id = 0;
+        ev.dst_master_instance_id = 1;
+        ev.value = MESSAGE_CHUNK_SIZE as i32 + 1;
+        assert_eq!(decoder.process(&ev), None);
+        ev.skill_id = 0;
+        assert_eq!(decoder.process(&ev), None);
+    }
+}
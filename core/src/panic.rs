@@ -0,0 +1,57 @@
+use std::{
+    any::Any,
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+static POISONED: AtomicBool = AtomicBool::new(false);
+
+/// Returns true if one of the callbacks panicked.
+/// ### Remarks
+/// Panics are not allowed to unwind into arcdps. The callbacks generated by
+/// [arcdps_export!](crate::arcdps_export) catch them, log the panic message and
+/// skip every callback afterwards, as the plugin state can't be trusted
+/// anymore. Callbacks that are registered with a `raw_` field are not
+/// guarded.
+pub fn is_poisoned() -> bool {
+    POISONED.load(Ordering::Acquire)
+}
+
+/// Runs a callback and catches any panic. Returns [`None`] if the callback
+/// panicked or a previous callback panicked.
+#[doc(hidden)]
+pub fn __catch_panic<R>(
+    #[cfg_attr(not(feature = "log"), allow(unused_variables))] callback: &str,
+    f: impl FnOnce() -> R,
+) -> Option<R> {
+    if is_poisoned() {
+        return None;
+    }
+
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(res) => Some(res),
+        Err(payload) => {
+            POISONED.store(true, Ordering::Release);
+            #[cfg(feature = "log")]
+            log::error!(
+                "{} panicked, skipping all further callbacks: {}",
+                callback,
+                panic_message(payload.as_ref())
+            );
+            #[cfg(not(feature = "log"))]
+            let _ = payload;
+            None
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "log"), allow(dead_code))]
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg
+    } else {
+        "Box<dyn Any>"
+    }
+}
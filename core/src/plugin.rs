@@ -0,0 +1,215 @@
+use std::{
+    error::Error,
+    ffi::c_void,
+    ptr::NonNull,
+    sync::{Mutex, PoisonError, TryLockError},
+};
+
+use crate::*;
+
+/// A plugin that owns its state.
+///
+/// Pass the type to the `plugin` field of [arcdps_export!] instead of
+/// registering the callbacks one by one. The macro constructs the plugin with
+/// [`Plugin::init`] and forwards every callback to the instance.
+/// All callbacks have a default implementation that does nothing, so only
+/// the ones that are needed have to be implemented.
+///
+/// ### Remarks
+/// The instance is guarded by a mutex, because arcdps calls the callbacks from
+/// different threads. The combat callbacks and [`Plugin::plugin_message`]
+/// don't wait for the mutex: events that arrive while another callback runs,
+/// for example during a long [`Plugin::imgui`] frame or when a callback emits
+/// an event with [emit_local], are skipped. All other callbacks wait and must
+/// not trigger other callbacks of the same plugin synchronously.
+///
+/// Callbacks that fire before [`Plugin::init`] succeeded or after
+/// [`Plugin::release`] are dropped. This includes
+/// [`Plugin::unofficial_extras_init`], if unofficial extras gets loaded before
+/// arcdps initializes the plugin.
+///
+/// ```no_run
+/// use std::{error::Error, ffi::c_void, ptr::NonNull};
+///
+/// use arcdps::{Agent, CombatEvent};
+///
+/// struct Meter {
+///     damage: i64,
+/// }
+///
+/// impl arcdps::Plugin for Meter {
+///     fn init(_swapchain: Option<NonNull<c_void>>) -> Result<Self, Box<dyn Error>> {
+///         Ok(Self { damage: 0 })
+///     }
+///
+///     fn combat(
+///         &mut self,
+///         ev: Option<&CombatEvent>,
+///         _src: Option<Agent>,
+///         _dst: Option<Agent>,
+///         _skill_name: Option<&'static str>,
+///         _id: u64,
+///         _revision: u64,
+///     ) {
+///         if let Some(ev) = ev {
+///             self.damage += ev.value as i64;
+///         }
+///     }
+/// }
+///
+/// arcdps::arcdps_export! {
+///     name: "example addon",
+///     sig: 123,
+///     plugin: crate::Meter,
+/// }
+/// # fn main() {}
+/// ```
+#[allow(unused_variables)]
+pub trait Plugin: Sized + Send + 'static {
+    /// Gets called on load. First parameter is id3dptr (swapchain) provided by
+    /// arcdps. Returning an error unloads the plugin, see [InitFunc].
+    fn init(swapchain: Option<NonNull<c_void>>) -> Result<Self, Box<dyn Error>>;
+
+    /// Gets called on unload, before the plugin gets dropped.
+    fn release(&mut self) {}
+
+    /// See [CombatCallback].
+    fn combat(
+        &mut self,
+        ev: Option<&CombatEvent>,
+        src: Option<Agent>,
+        dst: Option<Agent>,
+        skill_name: Option<&'static str>,
+        id: u64,
+        revision: u64,
+    ) {
+    }
+
+    /// See [CombatCallback].
+    fn combat_local(
+        &mut self,
+        ev: Option<&CombatEvent>,
+        src: Option<Agent>,
+        dst: Option<Agent>,
+        skill_name: Option<&'static str>,
+        id: u64,
+        revision: u64,
+    ) {
+    }
+
+    /// See [PluginMessageCallback].
+    fn plugin_message(&mut self, message: &PluginMessage) {}
+
+    /// See [ImguiCallback].
+    #[cfg(feature = "imgui")]
+    fn imgui(&mut self, ui: &imgui::Ui, not_character_select_or_loading: bool) {}
+
+    /// See [ImguiCallback].
+    #[cfg(not(feature = "imgui"))]
+    fn imgui(&mut self, not_character_select_or_loading: bool) {}
+
+    /// See [OptionsCallback].
+    #[cfg(feature = "imgui")]
+    fn options_end(&mut self, ui: &imgui::Ui) {}
+
+    /// See [OptionsCallback].
+    #[cfg(not(feature = "imgui"))]
+    fn options_end(&mut self) {}
+
+    /// See [OptionsWindowsCallback].
+    #[cfg(feature = "imgui")]
+    fn options_windows(&mut self, ui: &imgui::Ui, window_name: Option<&str>) -> bool {
+        false
+    }
+
+    /// See [OptionsWindowsCallback].
+    #[cfg(not(feature = "imgui"))]
+    fn options_windows(&mut self, window_name: Option<&str>) -> bool {
+        false
+    }
+
+    /// See [WndProcCallback].
+    fn wnd_filter(&mut self, key: usize, key_down: bool, prev_key_down: bool) -> bool {
+        true
+    }
+
+    /// See [WndProcCallback].
+    fn wnd_nofilter(&mut self, key: usize, key_down: bool, prev_key_down: bool) -> bool {
+        true
+    }
+
+    /// See [ExtrasInitFunc].
+    fn unofficial_extras_init(
+        &mut self,
+        account_name: Option<&str>,
+        extras_version: Option<&'static str>,
+    ) {
+    }
+
+    /// See [ExtrasSquadUpdateCallback].
+    fn unofficial_extras_squad_update(&mut self, users: UserInfoIter) {}
+
+    /// See [ExtrasChatMessageCallback].
+    fn unofficial_extras_chat_message(&mut self, msg: &SquadMessageInfo) {}
+
+    /// See [ExtrasChatMessage2Callback].
+    fn unofficial_extras_chat_message2(&mut self, msg: &ChatMessageInfo2) {}
+
+    /// See [ExtrasLanguageChangedCallback].
+    fn unofficial_extras_language_changed(&mut self, language: Language) {}
+
+    /// See [ExtrasKeyBindChangedCallback].
+    fn unofficial_extras_keybind_changed(&mut self, changed: KeyBindChanged) {}
+}
+
+/// Holds the instance of a [Plugin]. Used by [arcdps_export!].
+///
+/// One mutex guards the instance. [PluginHolder::with] blocks until the
+/// instance is free, [PluginHolder::try_with] gives up instead. The combat
+/// path uses the latter, so it never waits for a frame and re-entering from
+/// within a callback doesn't deadlock.
+#[doc(hidden)]
+pub struct PluginHolder<P>(Mutex<Option<P>>);
+
+impl<P: Plugin> PluginHolder<P> {
+    pub const fn new() -> Self {
+        Self(Mutex::new(None))
+    }
+
+    pub fn init(&self, swapchain: Option<NonNull<c_void>>) -> Result<(), Box<dyn Error>> {
+        let plugin = P::init(swapchain)?;
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner) = Some(plugin);
+        Ok(())
+    }
+
+    pub fn release(&self) {
+        let plugin = self.0.lock().unwrap_or_else(PoisonError::into_inner).take();
+        if let Some(mut plugin) = plugin {
+            plugin.release();
+        }
+    }
+
+    /// Runs `f` on the plugin instance. Returns [`None`] if the plugin is not
+    /// initialized.
+    pub fn with<R>(&self, f: impl FnOnce(&mut P) -> R) -> Option<R> {
+        let mut plugin = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        plugin.as_mut().map(f)
+    }
+
+    /// Runs `f` on the plugin instance if it is not in use. Returns [`None`]
+    /// if the plugin is not initialized or another callback is running.
+    pub fn try_with<R>(&self, f: impl FnOnce(&mut P) -> R) -> Option<R> {
+        let mut plugin = match self.0.try_lock() {
+            Ok(plugin) => plugin,
+            Err(TryLockError::Poisoned(err)) => err.into_inner(),
+            Err(TryLockError::WouldBlock) => return None,
+        };
+        plugin.as_mut().map(f)
+    }
+}
+
+impl<P: Plugin> Default for PluginHolder<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -92,6 +92,7 @@ pub type Export6 = unsafe extern "C" fn() -> u64;
 pub type Export7 = unsafe extern "C" fn() -> u64;
 pub type Export8 = Export3;
 pub type Export9 = unsafe extern "C" fn(&CombatEvent, u32);
+pub type Export10 = Export9;
 
 impl From<&RawAgent> for Agent<'_> {
     fn from(ag: &RawAgent) -> Self {
@@ -115,6 +116,7 @@ impl From<&RawAgent> for Agent<'_> {
 /// [`AgentOwned`].
 /// ```
 /// use arcdps::*;
+/// # let agent = Agent { name: None, id: 0, prof: 0, elite: 0, self_: 0, team: 0 };
 /// let agent: AgentOwned = agent.into();
 /// ```
 #[derive(Debug, Copy, Clone)]
@@ -187,6 +189,80 @@ pub struct CombatEvent {
     pub pad64: u8,
 }
 
+/// The layout of a [CombatEvent] in revision 0. Convert it to the current
+/// layout with [`CombatEvent::normalized`] or [`From`].
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct CombatEventRev0 {
+    pub time: u64,
+    pub src_agent: usize,
+    pub dst_agent: usize,
+    pub value: i32,
+    pub buff_dmg: i32,
+    pub overstack_value: u16,
+    pub skill_id: u16,
+    pub src_instance_id: u16,
+    pub dst_instance_id: u16,
+    pub src_master_instance_id: u16,
+    pub iss_offset: u8,
+    pub iss_offset_target: u8,
+    pub iss_bd_offset: u8,
+    pub iss_bd_offset_target: u8,
+    pub iss_alt_offset: u8,
+    pub iss_alt_offset_target: u8,
+    pub skar: u8,
+    pub skar_alt: u8,
+    pub skar_use_alt: u8,
+    pub iff: u8,
+    pub buff: u8,
+    pub result: u8,
+    pub is_activation: u8,
+    pub is_buff_remove: u8,
+    pub is_ninety: u8,
+    pub is_fifty: u8,
+    pub is_moving: u8,
+    pub is_statechange: u8,
+    pub is_flanking: u8,
+    pub is_shields: u8,
+    pub is_off_cycle: u8,
+    pub pad64: u8,
+}
+
+impl From<&CombatEventRev0> for CombatEvent {
+    fn from(ev: &CombatEventRev0) -> Self {
+        CombatEvent {
+            time: ev.time,
+            src_agent: ev.src_agent,
+            dst_agent: ev.dst_agent,
+            value: ev.value,
+            buff_dmg: ev.buff_dmg,
+            overstack_value: ev.overstack_value as u32,
+            skill_id: ev.skill_id as u32,
+            src_instance_id: ev.src_instance_id,
+            dst_instance_id: ev.dst_instance_id,
+            src_master_instance_id: ev.src_master_instance_id,
+            dst_master_instance_id: 0,
+            iff: ev.iff,
+            buff: ev.buff,
+            result: ev.result,
+            is_activation: ev.is_activation,
+            is_buff_remove: ev.is_buff_remove,
+            is_ninety: ev.is_ninety,
+            is_fifty: ev.is_fifty,
+            is_moving: ev.is_moving,
+            is_statechange: ev.is_statechange,
+            is_flanking: ev.is_flanking,
+            is_shields: ev.is_shields,
+            is_off_cycle: ev.is_off_cycle,
+            pad61: 0,
+            pad62: 0,
+            pad63: 0,
+            pad64: 0,
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct RawAgent {
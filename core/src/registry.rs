@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+
+use crate::{helpers::CombatEventArgs, Agent, CombatEvent};
+
+/// A player that was announced by arcdps. See [AgentRegistry].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct AgentRecord {
+    /// The agent id, as used in `src_agent` and `dst_agent`.
+    pub id: usize,
+    /// The instance id, as used in `src_instance_id` and `dst_instance_id`.
+    pub instance_id: u16,
+    pub character_name: Option<String>,
+    /// Account name, without leading ':'.
+    pub account_name: Option<String>,
+    pub prof: u32,
+    pub elite: u32,
+    pub team: u16,
+    pub subgroup: u16,
+    /// Whether this is the player of the local client.
+    pub is_self: bool,
+}
+
+/// Keeps track of the players arcdps announces with the agent added and
+/// removed notifications.
+///
+/// ```
+/// use arcdps::{helpers::CombatEventArgs, AgentRegistry};
+///
+/// fn combat(registry: &mut AgentRegistry, args: &CombatEventArgs) {
+///     registry.process(args);
+///     if let Some(me) = registry.self_agent() {
+///         println!("playing as {:?}", me.character_name);
+///     }
+/// }
+/// ```
+///
+/// ### Remarks
+/// Only events with `ev == None` change the registry, all others are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct AgentRegistry {
+    agents: HashMap<usize, AgentRecord>,
+    instance_ids: HashMap<u16, usize>,
+    target: Option<usize>,
+}
+
+impl AgentRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Updates the registry with the arguments of a combat callback. Returns
+    /// true if the arguments were a notification.
+    pub fn process(&mut self, args: &CombatEventArgs) -> bool {
+        self.update(args.ev, args.src, args.dst)
+    }
+
+    /// Updates the registry with the arguments of a combat callback. Returns
+    /// true if the arguments were a notification.
+    pub fn update(
+        &mut self,
+        ev: Option<&CombatEvent>,
+        src: Option<Agent>,
+        dst: Option<Agent>,
+    ) -> bool {
+        let (None, Some(src)) = (ev, src) else {
+            return false;
+        };
+
+        match (src.elite, src.prof) {
+            (0, 0) => self.remove(src.id),
+            (0, _) => {
+                let Some(dst) = dst else {
+                    return false;
+                };
+                self.add(AgentRecord {
+                    id: src.id,
+                    instance_id: dst.id as u16,
+                    character_name: src.name.map(ToString::to_string),
+                    account_name: dst
+                        .name
+                        .map(|name| name.trim_start_matches(':').to_string()),
+                    prof: dst.prof,
+                    elite: dst.elite,
+                    team: src.team,
+                    subgroup: dst.team,
+                    is_self: dst.self_ != 0,
+                });
+            }
+            (1, _) => self.target = (src.id != 0).then_some(src.id),
+            _ => return false,
+        }
+        true
+    }
+
+    fn add(&mut self, agent: AgentRecord) {
+        if let Some(old) = self.agents.remove(&agent.id) {
+            self.remove_instance_id(&old);
+        }
+        self.instance_ids.insert(agent.instance_id, agent.id);
+        self.agents.insert(agent.id, agent);
+    }
+
+    fn remove(&mut self, id: usize) {
+        if let Some(old) = self.agents.remove(&id) {
+            self.remove_instance_id(&old);
+        }
+    }
+
+    /// Instance ids get reused, so only remove the mapping if it still
+    /// belongs to the agent.
+    fn remove_instance_id(&mut self, agent: &AgentRecord) {
+        if self.instance_ids.get(&agent.instance_id) == Some(&agent.id) {
+            self.instance_ids.remove(&agent.instance_id);
+        }
+    }
+
+    /// Returns the player with the given agent id.
+    pub fn get(&self, id: usize) -> Option<&AgentRecord> {
+        self.agents.get(&id)
+    }
+
+    /// Returns the player with the given instance id.
+    pub fn by_instance_id(&self, instance_id: u16) -> Option<&AgentRecord> {
+        self.instance_ids
+            .get(&instance_id)
+            .and_then(|id| self.agents.get(id))
+    }
+
+    /// Returns the player of the local client.
+    pub fn self_agent(&self) -> Option<&AgentRecord> {
+        self.agents.values().find(|agent| agent.is_self)
+    }
+
+    /// Returns the agent id of the current target of the local player.
+    pub fn target(&self) -> Option<usize> {
+        self.target
+    }
+
+    /// Iterates over all tracked players in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = &AgentRecord> {
+        self.agents.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.agents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.agents.is_empty()
+    }
+
+    /// Removes all players and the target.
+    pub fn clear(&mut self) {
+        self.agents.clear();
+        self.instance_ids.clear();
+        self.target = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::test_events::at;
+
+    const ID: usize = 100;
+
+    fn added(id: usize, instance_id: usize, is_self: bool) -> (Agent<'static>, Agent<'static>) {
+        let src = Agent {
+            name: Some("Char Name"),
+            id,
+            prof: 1,
+            elite: 0,
+            self_: 0,
+            team: 7,
+        };
+        let dst = Agent {
+            name: Some(":Account.1234"),
+            id: instance_id,
+            prof: 1,
+            elite: 27,
+            self_: is_self as u32,
+            team: 3,
+        };
+        (src, dst)
+    }
+
+    fn removed(id: usize) -> Agent<'static> {
+        Agent {
+            name: None,
+            id,
+            prof: 0,
+            elite: 0,
+            self_: 0,
+            team: 0,
+        }
+    }
+
+    fn target(id: usize) -> Agent<'static> {
+        Agent {
+            name: None,
+            id,
+            prof: 0,
+            elite: 1,
+            self_: 0,
+            team: 0,
+        }
+    }
+
+    fn registry(notifications: &[(Agent<'static>, Option<Agent<'static>>)]) -> AgentRegistry {
+        let mut registry = AgentRegistry::new();
+        for (src, dst) in notifications {
+            assert!(registry.update(None, Some(*src), *dst));
+        }
+        registry
+    }
+
+    #[test]
+    fn adds_players() {
+        let (src, dst) = added(ID, 5, true);
+        let registry = registry(&[(src, Some(dst))]);
+
+        let player = registry.get(ID).unwrap();
+        assert_eq!(player.instance_id, 5);
+        assert_eq!(player.character_name.as_deref(), Some("Char Name"));
+        assert_eq!(player.account_name.as_deref(), Some("Account.1234"));
+        assert_eq!((player.prof, player.elite), (1, 27));
+        assert_eq!((player.team, player.subgroup), (7, 3));
+        assert!(player.is_self);
+        assert_eq!(registry.self_agent().map(|me| me.id), Some(ID));
+        assert_eq!(registry.by_instance_id(5).map(|player| player.id), Some(ID));
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn readd_moves_instance_id() {
+        let (src, dst) = added(ID, 5, false);
+        let (_, moved) = added(ID, 6, false);
+        let registry = registry(&[(src, Some(dst)), (src, Some(moved))]);
+
+        assert_eq!(registry.get(ID).unwrap().instance_id, 6);
+        assert!(registry.by_instance_id(5).is_none());
+        assert_eq!(registry.by_instance_id(6).map(|player| player.id), Some(ID));
+        assert!(registry.self_agent().is_none());
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn removes_players() {
+        let (src, dst) = added(ID, 5, true);
+        // another player that got the instance id before the removal
+        let (other, other_dst) = added(ID + 1, 5, false);
+        let reused = registry(&[
+            (src, Some(dst)),
+            (other, Some(other_dst)),
+            (removed(ID), None),
+        ]);
+
+        assert!(reused.get(ID).is_none());
+        assert!(reused.self_agent().is_none());
+        assert_eq!(
+            reused.by_instance_id(5).map(|player| player.id),
+            Some(ID + 1)
+        );
+
+        let cleared = registry(&[(src, Some(dst)), (removed(ID), None)]);
+        assert!(cleared.by_instance_id(5).is_none());
+        assert!(cleared.is_empty());
+    }
+
+    #[test]
+    fn tracks_target() {
+        let mut registry = registry(&[(target(200), None)]);
+        assert_eq!(registry.target(), Some(200));
+        assert!(registry.is_empty());
+
+        registry.update(None, Some(target(0)), None);
+        assert_eq!(registry.target(), None);
+    }
+
+    #[test]
+    fn ignores_events() {
+        let (src, dst) = added(ID, 5, false);
+        let ev = at(1).build();
+        let mut registry = AgentRegistry::new();
+
+        assert!(!registry.update(Some(&ev), Some(src), Some(dst)));
+        assert!(!registry.update(None, None, None));
+        assert!(registry.is_empty());
+    }
+}
@@ -0,0 +1,228 @@
+use std::{
+    error::Error,
+    ffi::{c_char, c_void, CStr, CString},
+    ptr::{null_mut, NonNull},
+    sync::{
+        atomic::{AtomicPtr, AtomicU32, Ordering},
+        Mutex, PoisonError,
+    },
+};
+
+use crate::{helpers, ArcDpsExport, GetProcAddress, HANDLE, PCCHAR};
+
+/// Names of the exports of arcdps, indexed by their number.
+const EXPORT_NAMES: [&CStr; 11] = [
+    c"e0", c"e1", c"e2", c"e3", c"e4", c"e5", c"e6", c"e7", c"e8", c"e9", c"e10",
+];
+
+/// The state arcdps hands to the plugin on load.
+///
+/// Everything is set in `get_init_addr` and reset on release, so a callback
+/// that fires before init or after release sees an empty state instead of
+/// dangling pointers.
+struct Runtime {
+    /// Module handle of arcdps. Null while the plugin is not loaded.
+    arcdps: AtomicPtr<c_void>,
+    version: AtomicPtr<c_char>,
+    swapchain: AtomicPtr<c_void>,
+    /// The sig of the plugin from its export table.
+    sig: AtomicU32,
+    /// Resolved exports, indexed by their number. Null if not resolved yet.
+    exports: [AtomicPtr<c_void>; EXPORT_NAMES.len()],
+    /// The export table returned when init fails. It has to stay alive until
+    /// arcdps read the error message.
+    error_export: Mutex<Option<Box<ErrorExport>>>,
+    #[cfg(feature = "imgui")]
+    ui: std::sync::OnceLock<UiHolder>,
+    /// Whether imgui was set up and the plugin was not released since.
+    #[cfg(feature = "imgui")]
+    ui_ready: std::sync::atomic::AtomicBool,
+    /// The render thread, the first thread that asked for the ui since load.
+    #[cfg(feature = "imgui")]
+    ui_thread: Mutex<Option<std::thread::ThreadId>>,
+}
+
+static RUNTIME: Runtime = Runtime {
+    arcdps: AtomicPtr::new(null_mut()),
+    version: AtomicPtr::new(null_mut()),
+    swapchain: AtomicPtr::new(null_mut()),
+    sig: AtomicU32::new(0),
+    exports: [const { AtomicPtr::new(null_mut()) }; EXPORT_NAMES.len()],
+    error_export: Mutex::new(None),
+    #[cfg(feature = "imgui")]
+    ui: std::sync::OnceLock::new(),
+    #[cfg(feature = "imgui")]
+    ui_ready: std::sync::atomic::AtomicBool::new(false),
+    #[cfg(feature = "imgui")]
+    ui_thread: Mutex::new(None),
+};
+
+struct ErrorExport {
+    export: ArcDpsExport,
+    _message: CString,
+}
+
+// SAFETY: the export table only points to static strings and the owned
+// message
+unsafe impl Send for ErrorExport {}
+
+#[cfg(feature = "imgui")]
+struct UiHolder(imgui::Ui<'static>);
+
+// SAFETY: __ui only hands out the Ui on the render thread, the holder itself is
+// never accessed from other threads
+#[cfg(feature = "imgui")]
+unsafe impl Send for UiHolder {}
+#[cfg(feature = "imgui")]
+unsafe impl Sync for UiHolder {}
+
+/// Returns true between `get_init_addr` and release.
+pub(crate) fn is_loaded() -> bool {
+    !RUNTIME.arcdps.load(Ordering::Acquire).is_null()
+}
+
+/// Returns the sig of the plugin.
+pub(crate) fn sig() -> u32 {
+    RUNTIME.sig.load(Ordering::Acquire)
+}
+
+/// Returns the address of an arcdps export. Returns null if the plugin is not
+/// loaded or arcdps doesn't provide the export.
+pub(crate) fn export(index: usize) -> *mut c_void {
+    let arcdps = RUNTIME.arcdps.load(Ordering::Acquire);
+    if arcdps.is_null() {
+        return null_mut();
+    }
+    let cached = RUNTIME.exports[index].load(Ordering::Acquire);
+    if !cached.is_null() {
+        return cached;
+    }
+    let func = unsafe { GetProcAddress(arcdps, EXPORT_NAMES[index].as_ptr()) };
+    RUNTIME.exports[index].store(func, Ordering::Release);
+    func
+}
+
+fn reset_exports() {
+    for export in &RUNTIME.exports {
+        export.store(null_mut(), Ordering::Release);
+    }
+}
+
+#[doc(hidden)]
+pub unsafe fn __init(
+    arc_version: PCCHAR,
+    arcdps: HANDLE,
+    swapchain: *mut c_void,
+    sig: u32,
+    #[cfg_attr(not(feature = "log"), allow(unused_variables))] name: &'static str,
+) {
+    reset_exports();
+    RUNTIME.sig.store(sig, Ordering::Release);
+    RUNTIME.version.store(arc_version, Ordering::Release);
+    RUNTIME.swapchain.store(swapchain, Ordering::Release);
+    RUNTIME.arcdps.store(arcdps, Ordering::Release);
+    #[cfg(feature = "log")]
+    let _ = log::set_boxed_logger(Box::new(crate::logging::ArcdpsLogger::new(name)))
+        .map(|()| log::set_max_level(log::LevelFilter::Trace));
+}
+
+/// Sets up imgui with the context of arcdps.
+#[cfg(feature = "imgui")]
+#[doc(hidden)]
+pub unsafe fn __init_imgui(
+    ctx: *mut imgui::sys::ImGuiContext,
+    malloc: Option<unsafe extern "C" fn(sz: usize, user_data: *mut c_void) -> *mut c_void>,
+    free: Option<unsafe extern "C" fn(ptr: *mut c_void, user_data: *mut c_void)>,
+) {
+    imgui::sys::igSetCurrentContext(ctx);
+    imgui::sys::igSetAllocatorFunctions(malloc, free, null_mut());
+    // replacing the context would end the frame of the current one. The
+    // context belongs to arcdps, so it must never be dropped.
+    RUNTIME.ui.get_or_init(|| {
+        let ctx = Box::leak(Box::new(imgui::Context::current()));
+        UiHolder(imgui::Ui::from_ctx(ctx))
+    });
+    RUNTIME.ui_ready.store(true, Ordering::Release);
+}
+
+/// Returns the [imgui::Ui] for the ui callbacks. Returns [`None`] before the
+/// plugin got loaded, after it got released and on every thread other than the
+/// render thread. The render thread is the first thread that calls this after
+/// the plugin got loaded, arcdps calls all ui callbacks on it.
+#[cfg(feature = "imgui")]
+#[doc(hidden)]
+pub fn __ui() -> Option<&'static imgui::Ui<'static>> {
+    if !RUNTIME.ui_ready.load(Ordering::Acquire) {
+        return None;
+    }
+    let current = std::thread::current().id();
+    let mut ui_thread = RUNTIME
+        .ui_thread
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    if *ui_thread.get_or_insert(current) != current {
+        return None;
+    }
+    RUNTIME.ui.get().map(|ui| &ui.0)
+}
+
+#[doc(hidden)]
+pub fn __swapchain() -> Option<NonNull<c_void>> {
+    NonNull::new(RUNTIME.swapchain.load(Ordering::Acquire))
+}
+
+/// Returns the export table that tells arcdps that init failed with `error`.
+#[doc(hidden)]
+pub fn __init_failed(export: &ArcDpsExport, error: &dyn Error) -> *const ArcDpsExport {
+    let message = CString::new(error.to_string().replace('\0', ""))
+        .expect("nul bytes were removed from the message");
+    let error_export = Box::new(ErrorExport {
+        export: ArcDpsExport {
+            size: message.as_ptr() as _,
+            sig: 0,
+            imgui_version: export.imgui_version,
+            out_name: export.out_name,
+            out_build: export.out_build,
+            wnd_nofilter: None,
+            combat: None,
+            imgui: None,
+            options_end: None,
+            combat_local: None,
+            wnd_filter: None,
+            options_windows: None,
+        },
+        _message: message,
+    });
+    let ptr = &error_export.export as *const _;
+    *RUNTIME
+        .error_export
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = Some(error_export);
+    ptr
+}
+
+/// Forgets everything arcdps handed to the plugin. The exports do nothing
+/// afterwards.
+#[doc(hidden)]
+pub fn __release() {
+    RUNTIME.arcdps.store(null_mut(), Ordering::Release);
+    RUNTIME.version.store(null_mut(), Ordering::Release);
+    RUNTIME.swapchain.store(null_mut(), Ordering::Release);
+    #[cfg(feature = "imgui")]
+    {
+        RUNTIME.ui_ready.store(false, Ordering::Release);
+        *RUNTIME
+            .ui_thread
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = None;
+    }
+    reset_exports();
+}
+
+/// Returns the loaded arcdps version. Returns [`None`] if the plugin is not
+/// loaded.
+pub fn arcdps_version() -> Option<&'static str> {
+    let version = RUNTIME.version.load(Ordering::Acquire);
+    // SAFETY: arcdps keeps the version alive while the plugin is loaded
+    unsafe { helpers::get_str_from_pc_char(version) }
+}
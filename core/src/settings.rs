@@ -0,0 +1,249 @@
+use crate::{e6, e7};
+
+/// The ui settings of arcdps, from `e6`.
+///
+/// ```no_run
+/// use arcdps::UiSettings;
+///
+/// # #[cfg(feature = "imgui")]
+/// fn imgui(ui: &arcdps::imgui::Ui, not_character_select_or_loading: bool) {
+///     if !UiSettings::read().should_draw(not_character_select_or_loading) {
+///         return;
+///     }
+///     ui.text("visible like the windows of arcdps");
+/// }
+/// ```
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct UiSettings {
+    /// The user hid the ui of arcdps.
+    pub hidden: bool,
+    /// Windows are drawn during loading screens and in character select.
+    pub always_draw: bool,
+    /// Windows can only be moved while holding the modifiers.
+    pub modifiers_move_lock: bool,
+    /// Windows can only be clicked while holding the modifiers.
+    pub modifiers_click_lock: bool,
+    /// Windows close when pressing escape.
+    pub close_with_esc: bool,
+}
+
+impl UiSettings {
+    pub fn from_raw(raw: u64) -> Self {
+        Self {
+            hidden: raw & 1 != 0,
+            always_draw: raw & (1 << 1) != 0,
+            modifiers_move_lock: raw & (1 << 2) != 0,
+            modifiers_click_lock: raw & (1 << 3) != 0,
+            close_with_esc: raw & (1 << 4) != 0,
+        }
+    }
+
+    /// Reads the current settings. Returns the default settings if the plugin
+    /// is not loaded.
+    pub fn read() -> Self {
+        Self::from_raw(unsafe { e6() })
+    }
+
+    /// Whether windows should be drawn, given the argument of the
+    /// [ImguiCallback](crate::ImguiCallback).
+    pub fn should_draw(&self, not_character_select_or_loading: bool) -> bool {
+        !self.hidden && (not_character_select_or_loading || self.always_draw)
+    }
+
+    /// Returns the window flags that apply the move and click locks of
+    /// arcdps.
+    #[cfg(feature = "imgui")]
+    pub fn window_flags(&self, modifiers: &ModifierTracker) -> imgui::WindowFlags {
+        let mut flags = imgui::WindowFlags::empty();
+        if !modifiers.is_held() {
+            if self.modifiers_move_lock {
+                flags |= imgui::WindowFlags::NO_MOVE;
+            }
+            if self.modifiers_click_lock {
+                flags |= imgui::WindowFlags::NO_MOUSE_INPUTS;
+            }
+        }
+        flags
+    }
+}
+
+/// The modifier keys of arcdps, from `e7`. The keys are virtual key codes.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Modifiers {
+    pub modifier1: u16,
+    pub modifier2: u16,
+    /// The key to select multiple entries, e.g. in the squad panel.
+    pub modifier_multi: u16,
+}
+
+impl Modifiers {
+    pub fn from_raw(raw: u64) -> Self {
+        Self {
+            modifier1: raw as u16,
+            modifier2: (raw >> 16) as u16,
+            modifier_multi: (raw >> 32) as u16,
+        }
+    }
+
+    /// Reads the current modifiers. Returns no modifiers if the plugin is not
+    /// loaded.
+    pub fn read() -> Self {
+        Self::from_raw(unsafe { e7() })
+    }
+}
+
+/// Tracks whether the modifiers of arcdps are held, from the key events of a
+/// [WndProcCallback](crate::WndProcCallback).
+///
+/// The modifiers can change at any time, so pass the current ones to
+/// [`ModifierTracker::set_modifiers`] once per frame, e.g. from the
+/// [ImguiCallback](crate::ImguiCallback). Until then, no modifier is held.
+///
+/// ```no_run
+/// use std::sync::Mutex;
+///
+/// use arcdps::{ModifierTracker, Modifiers};
+///
+/// static MODIFIERS: Mutex<ModifierTracker> = Mutex::new(ModifierTracker::new());
+///
+/// fn imgui(_not_character_select_or_loading: bool) {
+///     MODIFIERS.lock().unwrap().set_modifiers(Modifiers::read());
+/// }
+///
+/// fn wnd_filter(key: usize, key_down: bool, _prev_key_down: bool) -> bool {
+///     let mut modifiers = MODIFIERS.lock().unwrap();
+///     modifiers.process(key, key_down);
+///     if key_down && key == 0x4B && modifiers.is_held() {
+///         // modifiers + K, don't pass it to the game
+///         return false;
+///     }
+///     true
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ModifierTracker {
+    modifiers: Option<Modifiers>,
+    modifier1: bool,
+    modifier2: bool,
+    modifier_multi: bool,
+}
+
+impl ModifierTracker {
+    pub const fn new() -> Self {
+        Self {
+            modifiers: None,
+            modifier1: false,
+            modifier2: false,
+            modifier_multi: false,
+        }
+    }
+
+    /// Returns the modifiers that were set last.
+    pub fn modifiers(&self) -> Option<Modifiers> {
+        self.modifiers
+    }
+
+    /// Sets the current modifiers. Modifiers that changed are not held.
+    pub fn set_modifiers(&mut self, modifiers: Modifiers) {
+        let old = self.modifiers.unwrap_or_default();
+        self.modifier1 &= old.modifier1 == modifiers.modifier1;
+        self.modifier2 &= old.modifier2 == modifiers.modifier2;
+        self.modifier_multi &= old.modifier_multi == modifiers.modifier_multi;
+        self.modifiers = Some(modifiers);
+    }
+
+    /// Updates the state with a key event. Returns whether the key is one of
+    /// the modifiers.
+    pub fn process(&mut self, key: usize, key_down: bool) -> bool {
+        let Some(modifiers) = self.modifiers else {
+            return false;
+        };
+        let mut is_modifier = false;
+        for (modifier, held) in [
+            (modifiers.modifier1, &mut self.modifier1),
+            (modifiers.modifier2, &mut self.modifier2),
+            (modifiers.modifier_multi, &mut self.modifier_multi),
+        ] {
+            if modifier != 0 && modifier as usize == key {
+                *held = key_down;
+                is_modifier = true;
+            }
+        }
+        is_modifier
+    }
+
+    /// Whether modifier1 and modifier2 are held, like arcdps requires for its
+    /// hotkeys. A modifier that is not set counts as held.
+    pub fn is_held(&self) -> bool {
+        let Some(modifiers) = self.modifiers else {
+            return false;
+        };
+        (modifiers.modifier1 == 0 || self.modifier1) && (modifiers.modifier2 == 0 || self.modifier2)
+    }
+
+    /// Whether the multi select modifier is held.
+    pub fn is_multi_held(&self) -> bool {
+        self.modifier_multi
+    }
+
+    /// Forgets all held keys, e.g. when the game lost focus. Keeps the
+    /// modifiers.
+    pub fn reset(&mut self) {
+        *self = Self {
+            modifiers: self.modifiers,
+            ..Self::new()
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHIFT: u16 = 0x10;
+    const ALT: u16 = 0x12;
+
+    #[test]
+    fn tracks_modifiers() {
+        let mut tracker = ModifierTracker::new();
+        assert!(!tracker.process(SHIFT as usize, true));
+        assert!(!tracker.is_held());
+
+        tracker.set_modifiers(Modifiers {
+            modifier1: SHIFT,
+            modifier2: ALT,
+            modifier_multi: 0,
+        });
+        assert!(tracker.process(SHIFT as usize, true));
+        assert!(!tracker.process(0x4B, true));
+        assert!(!tracker.is_held());
+        tracker.process(ALT as usize, true);
+        assert!(tracker.is_held());
+
+        tracker.process(ALT as usize, false);
+        assert!(!tracker.is_held());
+    }
+
+    #[test]
+    fn unset_modifiers_count_as_held() {
+        let mut tracker = ModifierTracker::new();
+        tracker.set_modifiers(Modifiers {
+            modifier1: SHIFT,
+            modifier2: 0,
+            modifier_multi: 0,
+        });
+        tracker.process(SHIFT as usize, true);
+        assert!(tracker.is_held());
+
+        tracker.set_modifiers(Modifiers {
+            modifier1: ALT,
+            modifier2: 0,
+            modifier_multi: 0,
+        });
+        assert!(!tracker.is_held());
+        tracker.reset();
+        assert_eq!(tracker.modifiers().map(|m| m.modifier1), Some(ALT));
+    }
+}
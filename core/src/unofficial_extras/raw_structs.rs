@@ -324,7 +324,7 @@ pub union RawChatMessageInfo2 {
 pub type RawSquadUpdateCallbackSignature = unsafe extern "C" fn(*const RawUserInfo, u64);
 pub type RawLanguageChangedCallbackSignature = unsafe extern "C" fn(Language);
 pub type RawKeyBindChangedCallbackSignature =
-    unsafe extern "C" fn(raw_structs_keybinds::KeyBindChanged);
+    unsafe extern "C" fn(raw_structs_keybinds::RawKeyBindChanged);
 pub type RawChatMessageCallbackSignature = unsafe extern "C" fn(*const RawSquadMessageInfo);
 pub type RawChatMessage2CallbackSignature =
     unsafe extern "C" fn(ChatMessageType, RawChatMessageInfo2);
@@ -347,6 +347,9 @@ use std::{
 pub type ExtrasSquadUpdateCallback = fn(UserInfoIter);
 pub type ExtrasChatMessageCallback = fn(&SquadMessageInfo);
 pub type ExtrasChatMessage2Callback = fn(&ChatMessageInfo2);
+/// Called whenever the language is changed and once directly after
+/// initialization with the current language.
+pub type ExtrasLanguageChangedCallback = fn(Language);
 pub type UserInfoIter<'a> = Map<Iter<'a, RawUserInfo>, UserConvert>;
 pub type UserConvert = for<'r> fn(&'r RawUserInfo) -> UserInfo<'r>;
 
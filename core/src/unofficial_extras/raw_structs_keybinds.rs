@@ -4,7 +4,7 @@
 #[allow(dead_code)]
 #[allow(non_camel_case_types)]
 #[repr(i32)]
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub enum KeyControl {
     // Movement tab
@@ -202,7 +202,7 @@ pub enum KeyControl {
 #[allow(dead_code)]
 #[allow(non_camel_case_types)]
 #[repr(i32)]
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub enum KeyCode {
     LeftAlt      = 0,
@@ -348,7 +348,7 @@ pub enum KeyCode {
 #[allow(dead_code)]
 #[allow(non_camel_case_types)]
 #[repr(i32)]
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub enum MouseCode {
     Mouse_1  = 0,
@@ -376,7 +376,7 @@ pub enum MouseCode {
 #[allow(dead_code)]
 #[allow(non_camel_case_types)]
 #[repr(i32)]
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub enum DeviceType {
     Unset    = 0,
@@ -387,42 +387,44 @@ pub enum DeviceType {
 #[allow(dead_code)]
 #[allow(non_camel_case_types)]
 #[repr(i32)]
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub enum Modifier_ {
     Modifier_Shift = 1,
     Modifier_Ctrl  = 2,
     Modifier_Alt   = 4,
 }
-pub type Modifier = i32;
 /// -> enum Modifier_
+pub type Modifier = i32;
 
 /// A single KeyBind
 #[repr(C)]
-#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
-pub struct Key {
+#[derive(Copy, Clone, Debug)]
+pub struct RawKey {
     /// 0|1|2 (0 = unset, 1 = MouseKey, 2 = KeyboardKey)
-    pub device_type: DeviceType,
-    /// MouseCode or KeyCode depending on [`device_type`]
+    /// -> enum DeviceType
+    pub device_type: i32,
+    /// MouseCode or KeyCode depending on [`RawKey::device_type`]
     pub code: i32,
     /// modifier flags (Bit 1 = Shift, Bit 2 = Ctrl, Bit 3 = Alt)
     pub modifier: Modifier,
 }
 
-#[allow(dead_code)]
 #[repr(C)]
-#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
-pub struct KeyBind {
-    primary: Key,
-    secondary: Key,
+#[derive(Copy, Clone, Debug)]
+pub struct RawKeyBind {
+    pub primary: RawKey,
+    pub secondary: RawKey,
 }
 
 #[repr(C)]
-#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
-pub struct KeyBindChanged {
-    key_control: KeyControl,
-    key_index: i32,
-    single_key: Key,
+#[derive(Copy, Clone, Debug)]
+pub struct RawKeyBindChanged {
+    /// -> enum KeyControl
+    pub key_control: i32,
+    /// 0 for the primary key, 1 for the secondary key
+    pub key_index: i32,
+    pub single_key: RawKey,
 }
 
 /// `key_index` is either 0 or 1, notating the primary and secondary key for the
@@ -431,6 +433,260 @@ pub struct KeyBindChanged {
 /// patterns. You can detect if it is disabled by checking if the
 /// `KeyBindChangedCallback` got called on startup.
 #[allow(dead_code)]
-pub type RawGetKeySignature = unsafe extern "C" fn(control: KeyControl, key_index: u32) -> Key;
+pub type RawGetKeySignature = unsafe extern "C" fn(control: KeyControl, key_index: u32) -> RawKey;
 #[allow(dead_code)]
-pub type RawGetKeyBindSignature = unsafe extern "C" fn(control: KeyControl) -> KeyBind;
+pub type RawGetKeyBindSignature = unsafe extern "C" fn(control: KeyControl) -> RawKeyBind;
+
+/// Called whenever a keybind is changed and once for every existing keybind
+/// after initialization. Changes of controls that are not known to this
+/// version of the bindings are skipped.
+pub type ExtrasKeyBindChangedCallback = fn(KeyBindChanged);
+
+/// The input a [`Key`] is bound to.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum KeyInput {
+    Unset,
+    Mouse(MouseCode),
+    Keyboard(KeyCode),
+    /// A code that is not known to this version of the bindings
+    Unknown {
+        device_type: i32,
+        code: i32,
+    },
+}
+
+/// Modifier keys that need to be held down together with a [`Key`].
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct KeyModifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+/// A single key of a keybind.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Key {
+    pub input: KeyInput,
+    pub modifiers: KeyModifiers,
+}
+
+/// The primary and secondary key of a keybind.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct KeyBind {
+    pub primary: Key,
+    pub secondary: Key,
+}
+
+/// A single key of a keybind that changed.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct KeyBindChanged {
+    pub key_control: KeyControl,
+    /// `true` if the secondary key changed, `false` for the primary key
+    pub secondary: bool,
+    pub key: Key,
+}
+
+impl TryFrom<i32> for KeyControl {
+    type Error = i32;
+
+    fn try_from(control: i32) -> Result<Self, Self::Error> {
+        match control {
+            // SAFETY: the ranges match exactly the discriminants of the enum
+            0..=12
+            | 14..=75
+            | 77..=78
+            | 81..=113
+            | 115
+            | 118..=128
+            | 148..=169
+            | 190..=195
+            | 197..=204
+            | 207..=214
+            | 220 => Ok(unsafe { std::mem::transmute::<i32, KeyControl>(control) }),
+            _ => Err(control),
+        }
+    }
+}
+
+impl TryFrom<i32> for DeviceType {
+    type Error = i32;
+
+    fn try_from(device_type: i32) -> Result<Self, Self::Error> {
+        match device_type {
+            0 => Ok(DeviceType::Unset),
+            1 => Ok(DeviceType::Mouse),
+            2 => Ok(DeviceType::Keyboard),
+            _ => Err(device_type),
+        }
+    }
+}
+
+impl TryFrom<i32> for KeyCode {
+    type Error = i32;
+
+    fn try_from(code: i32) -> Result<Self, Self::Error> {
+        match code {
+            // SAFETY: the ranges match exactly the discriminants of the enum
+            0..=43 | 48..=57 | 65..=141 | 200..=204 => {
+                Ok(unsafe { std::mem::transmute::<i32, KeyCode>(code) })
+            }
+            _ => Err(code),
+        }
+    }
+}
+
+impl TryFrom<i32> for MouseCode {
+    type Error = i32;
+
+    fn try_from(code: i32) -> Result<Self, Self::Error> {
+        match code {
+            // SAFETY: the range matches exactly the discriminants of the enum
+            0..=19 => Ok(unsafe { std::mem::transmute::<i32, MouseCode>(code) }),
+            _ => Err(code),
+        }
+    }
+}
+
+impl From<RawKey> for Key {
+    fn from(key: RawKey) -> Self {
+        let input = match DeviceType::try_from(key.device_type) {
+            Ok(DeviceType::Unset) => Some(KeyInput::Unset),
+            Ok(DeviceType::Mouse) => MouseCode::try_from(key.code).ok().map(KeyInput::Mouse),
+            Ok(DeviceType::Keyboard) => KeyCode::try_from(key.code).ok().map(KeyInput::Keyboard),
+            Err(_) => None,
+        };
+        let input = input.unwrap_or(KeyInput::Unknown {
+            device_type: key.device_type,
+            code: key.code,
+        });
+
+        Key {
+            input,
+            modifiers: KeyModifiers {
+                shift: key.modifier & Modifier_::Modifier_Shift as Modifier != 0,
+                ctrl: key.modifier & Modifier_::Modifier_Ctrl as Modifier != 0,
+                alt: key.modifier & Modifier_::Modifier_Alt as Modifier != 0,
+            },
+        }
+    }
+}
+
+impl From<RawKeyBind> for KeyBind {
+    fn from(bind: RawKeyBind) -> Self {
+        KeyBind {
+            primary: bind.primary.into(),
+            secondary: bind.secondary.into(),
+        }
+    }
+}
+
+/// Fails with the raw key control if it is not known.
+impl TryFrom<RawKeyBindChanged> for KeyBindChanged {
+    type Error = i32;
+
+    fn try_from(changed: RawKeyBindChanged) -> Result<Self, Self::Error> {
+        Ok(KeyBindChanged {
+            key_control: changed.key_control.try_into()?,
+            secondary: changed.key_index != 0,
+            key: changed.single_key.into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw_key(device_type: i32, code: i32, modifier: Modifier) -> RawKey {
+        RawKey {
+            device_type,
+            code,
+            modifier,
+        }
+    }
+
+    #[test]
+    fn key_from_raw() {
+        let key = Key::from(raw_key(
+            2,
+            KeyCode::F as i32,
+            Modifier_::Modifier_Ctrl as i32,
+        ));
+        assert_eq!(key.input, KeyInput::Keyboard(KeyCode::F));
+        assert_eq!(
+            key.modifiers,
+            KeyModifiers {
+                shift: false,
+                ctrl: true,
+                alt: false,
+            }
+        );
+
+        let key = Key::from(raw_key(1, MouseCode::Mouse_4 as i32, 0));
+        assert_eq!(key.input, KeyInput::Mouse(MouseCode::Mouse_4));
+        assert_eq!(Key::from(raw_key(0, 5, 0)).input, KeyInput::Unset);
+    }
+
+    #[test]
+    fn unknown_key_from_raw() {
+        assert_eq!(
+            Key::from(raw_key(2, 1000, 0)).input,
+            KeyInput::Unknown {
+                device_type: 2,
+                code: 1000,
+            }
+        );
+        assert_eq!(
+            Key::from(raw_key(7, 3, 0)).input,
+            KeyInput::Unknown {
+                device_type: 7,
+                code: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn key_bind_changed_from_raw() {
+        let changed = RawKeyBindChanged {
+            key_control: KeyControl::Skills_WeaponSkill1 as i32,
+            key_index: 1,
+            single_key: raw_key(2, KeyCode::_1 as i32, 0),
+        };
+        assert_eq!(
+            KeyBindChanged::try_from(changed),
+            Ok(KeyBindChanged {
+                key_control: KeyControl::Skills_WeaponSkill1,
+                secondary: true,
+                key: Key {
+                    input: KeyInput::Keyboard(KeyCode::_1),
+                    modifiers: KeyModifiers::default(),
+                },
+            })
+        );
+
+        let changed = RawKeyBindChanged {
+            key_control: 13,
+            ..changed
+        };
+        assert_eq!(KeyBindChanged::try_from(changed), Err(13));
+    }
+
+    #[test]
+    fn key_control_ranges() {
+        // one value per variant
+        let known = (-1..=256)
+            .filter_map(|control| KeyControl::try_from(control).ok())
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(known.len(), 167);
+        assert_eq!(
+            KeyControl::try_from(220),
+            Ok(KeyControl::Miscellaneous_MasterySkill)
+        );
+        assert_eq!(KeyControl::try_from(221), Err(221));
+    }
+}
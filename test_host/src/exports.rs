@@ -0,0 +1,141 @@
+//! Stub implementations of the functions arcdps exports to plugins.
+//!
+//! The arcdps crate resolves the exports with `GetProcAddress`. This module
+//! provides that symbol, so the stubs get resolved in place of the real
+//! exports when a plugin runs inside the test host.
+use std::{
+    ffi::{c_void, CStr},
+    sync::{Mutex, MutexGuard, OnceLock, PoisonError},
+};
+
+use arcdps::{CombatEvent, HMODULE, LPCSTR};
+
+/// Everything the stub exports record or return.
+#[derive(Debug, Default)]
+pub(crate) struct ExportState {
+    /// Lines logged to the arcdps log file with `e3`.
+    pub file_log: Vec<String>,
+    /// Lines logged to the arcdps log window with `e8`.
+    pub window_log: Vec<String>,
+    /// Events that were added with `e9`, together with the signature.
+    pub events: Vec<(CombatEvent, u32)>,
+    /// Events that were added with `e10`, together with the signature.
+    pub local_events: Vec<(CombatEvent, u32)>,
+    /// Null terminated wide string returned by `e0`.
+    pub ini_path: Vec<u16>,
+    /// Value returned by `e6`.
+    pub ui_settings: u64,
+    /// Value returned by `e7`.
+    pub modifiers: u64,
+    /// The color tables returned by `e5`.
+    pub colors: ColorTables,
+}
+
+/// The 5 color tables of `e5`, with 16 colors each.
+pub(crate) type ColorTables = [[[f32; 4]; 16]; 5];
+
+static STATE: Mutex<Option<ExportState>> = Mutex::new(None);
+
+pub(crate) fn with_state<R>(f: impl FnOnce(&mut ExportState) -> R) -> R {
+    let mut state = STATE.lock().unwrap_or_else(PoisonError::into_inner);
+    f(state.get_or_insert_with(Default::default))
+}
+
+pub(crate) fn reset_state() {
+    *STATE.lock().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// Serializes the hosts, as a plugin only exists once per process.
+static HOST: Mutex<()> = Mutex::new(());
+
+pub(crate) fn lock_host() -> MutexGuard<'static, ()> {
+    HOST.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Replacement for the windows function that arcdps plugins use to look up
+/// the arcdps exports.
+#[cfg(not(windows))]
+#[no_mangle]
+pub unsafe extern "system" fn GetProcAddress(_module: HMODULE, proc_name: LPCSTR) -> *mut c_void {
+    if proc_name.is_null() {
+        return std::ptr::null_mut();
+    }
+    match CStr::from_ptr(proc_name).to_bytes() {
+        b"e0" => e0 as *mut c_void,
+        b"e3" => e3 as *mut c_void,
+        b"e5" => e5 as *mut c_void,
+        b"e6" => e6 as *mut c_void,
+        b"e7" => e7 as *mut c_void,
+        b"e8" => e8 as *mut c_void,
+        b"e9" => e9 as *mut c_void,
+        b"e10" => e10 as *mut c_void,
+        _ => std::ptr::null_mut(),
+    }
+}
+
+unsafe fn read_str(s: *mut u8) -> String {
+    if s.is_null() {
+        return String::new();
+    }
+    CStr::from_ptr(s as _).to_string_lossy().into_owned()
+}
+
+unsafe extern "C" fn e0() -> *mut u16 {
+    with_state(|state| {
+        if state.ini_path.is_empty() {
+            state.ini_path.push(0);
+        }
+        state.ini_path.as_mut_ptr()
+    })
+}
+
+unsafe extern "C" fn e3(s: *mut u8) {
+    let line = read_str(s);
+    with_state(|state| state.file_log.push(line));
+}
+
+/// Returns the buffer the pointers of `e5` point to. arcdps keeps its tables
+/// alive, so the buffer is leaked once.
+fn color_buffer() -> *mut ColorTables {
+    static COLORS: OnceLock<usize> = OnceLock::new();
+    *COLORS.get_or_init(|| Box::into_raw(Box::<ColorTables>::default()) as usize)
+        as *mut ColorTables
+}
+
+unsafe extern "C" fn e5(out: *mut [*mut c_void; 5]) {
+    if out.is_null() {
+        return;
+    }
+    let colors = color_buffer();
+    // SAFETY: the buffer is leaked and only written here, plugins only read
+    // it after e5 returned
+    with_state(|state| *colors = state.colors);
+    for (i, ptr) in (*out).iter_mut().enumerate() {
+        *ptr = (*colors)[i].as_mut_ptr().cast();
+    }
+}
+
+unsafe extern "C" fn e6() -> u64 {
+    with_state(|state| state.ui_settings)
+}
+
+unsafe extern "C" fn e7() -> u64 {
+    with_state(|state| state.modifiers)
+}
+
+unsafe extern "C" fn e8(s: *mut u8) {
+    let line = read_str(s);
+    with_state(|state| state.window_log.push(line));
+}
+
+unsafe extern "C" fn e9(ev: *const CombatEvent, sig: u32) {
+    if let Some(ev) = ev.as_ref() {
+        with_state(|state| state.events.push((*ev, sig)));
+    }
+}
+
+unsafe extern "C" fn e10(ev: *const CombatEvent, sig: u32) {
+    if let Some(ev) = ev.as_ref() {
+        with_state(|state| state.local_events.push((*ev, sig)));
+    }
+}
@@ -0,0 +1,625 @@
+//! An in-process mock of arcdps to test plugins without launching the game.
+//!
+//! The [Host] loads a plugin that was built with
+//! [arcdps_export!](arcdps::arcdps_export) in the same binary, the same way
+//! arcdps does it. Afterwards the callbacks of the plugin can be driven
+//! directly from a test. The exports of arcdps (`e0`-`e10`) are replaced by
+//! stubs that record what the plugin sends to them, for example the lines
+//! that get logged via the `log` crate.
+//!
+//! ```
+//! use arcdps::{Agent, CombatEvent};
+//!
+//! arcdps::arcdps_export! {
+//!     name: "example addon",
+//!     sig: 123,
+//!     combat: crate::combat,
+//! }
+//!
+//! fn combat(
+//!     ev: Option<&CombatEvent>,
+//!     src: Option<Agent>,
+//!     _dst: Option<Agent>,
+//!     _skill_name: Option<&'static str>,
+//!     _id: u64,
+//!     _revision: u64,
+//! ) {
+//!     if ev.is_none() {
+//!         log::info!("agent {:?}", src.and_then(|src| src.name));
+//!     }
+//! }
+//!
+//! # fn main() {
+//! let host = arcdps_test_host::load!().unwrap();
+//! assert_eq!(host.name(), "example addon");
+//!
+//! let src = arcdps::AgentOwned {
+//!     name: Some("Character".to_string()),
+//!     id: 1,
+//!     prof: 1,
+//!     elite: 0,
+//!     self_: 1,
+//!     team: 0,
+//! };
+//! host.combat(None, Some(&src), None, None, 0, 1);
+//! assert!(host.file_log()[0].ends_with("agent Some(\"Character\")"));
+//! # }
+//! ```
+//!
+//! ### Remarks
+//! A plugin exists only once per process, so only one [Host] can be alive at
+//! a time. [Host::load] blocks until the previous host was dropped.
+//! Panics in callbacks poison the plugin for the rest of the process, see
+//! [arcdps::is_poisoned].
+
+mod exports;
+
+use std::{
+    ffi::{c_char, c_void, CStr, CString},
+    mem::MaybeUninit,
+    ptr::null_mut,
+    sync::MutexGuard,
+};
+
+use arcdps::{
+    AgentOwned, ArcDpsExport, ChatMessageInfo2, ChatMessageType, CombatEvent, InfoV3, Language,
+    NpcMessageInfo, RawAgent, RawChatMessageInfo2, RawExtrasAddonInfo, RawExtrasSubscriberInfo,
+    RawExtrasSubscriberInfoHeader, RawKeyBindChanged, RawNpcMessageInfo, RawSquadMessageInfo,
+    RawUserInfo, SquadMessageInfo, UserInfoOwned, WM_KEYDOWN, WM_KEYUP,
+};
+
+#[cfg(feature = "imgui")]
+type ImguiContext = arcdps::imgui::sys::ImGuiContext;
+#[cfg(not(feature = "imgui"))]
+type ImguiContext = c_void;
+
+type MallocFn = unsafe extern "C" fn(sz: usize, user_data: *mut c_void) -> *mut c_void;
+type FreeFn = unsafe extern "C" fn(ptr: *mut c_void, user_data: *mut c_void);
+
+/// Signature of the `get_init_addr` function generated by
+/// [arcdps_export!](arcdps::arcdps_export).
+pub type GetInitAddr =
+    unsafe extern "system" fn(
+        arc_version: *mut c_char,
+        imguictx: *mut ImguiContext,
+        id3dptr: *mut c_void,
+        arc_dll: *mut c_void,
+        mallocfn: Option<MallocFn>,
+        freefn: Option<FreeFn>,
+    ) -> unsafe extern "system" fn() -> *const ArcDpsExport;
+
+/// Signature of the `get_release_addr` function generated by
+/// [arcdps_export!](arcdps::arcdps_export).
+pub type GetReleaseAddr = extern "system" fn() -> unsafe extern "system" fn();
+
+/// Signature of the `arcdps_unofficial_extras_subscriber_init` function
+/// generated by [arcdps_export!](arcdps::arcdps_export).
+pub type ExtrasSubscriberInit =
+    unsafe extern "system" fn(&RawExtrasAddonInfo, *mut RawExtrasSubscriberInfoHeader);
+
+/// The arcdps version that gets passed to the plugin.
+pub const ARC_VERSION: &CStr = c"20250101.000000-000-x64-mock";
+
+/// Loads the plugin that was exported with
+/// [arcdps_export!](arcdps::arcdps_export) in the crate root of the calling
+/// crate. See [Host::load].
+#[macro_export]
+#[allow(clippy::crate_in_macro_def)]
+macro_rules! load {
+    () => {
+        // SAFETY: the functions are generated by arcdps_export!
+        unsafe {
+            $crate::Host::load(
+                crate::__arcdps_gen_export::get_init_addr,
+                crate::__arcdps_gen_export::get_release_addr,
+            )
+        }
+    };
+}
+
+/// A loaded plugin. The plugin gets released when this is dropped.
+pub struct Host {
+    export: &'static ArcDpsExport,
+    release: unsafe extern "system" fn(),
+    extras: Option<Box<RawExtrasSubscriberInfo<InfoV3>>>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl Host {
+    /// Loads a plugin the same way arcdps does it.
+    /// Returns the error message if the init function of the plugin failed.
+    ///
+    /// # Safety
+    /// The functions must be the ones generated by
+    /// [arcdps_export!](arcdps::arcdps_export).
+    pub unsafe fn load(init: GetInitAddr, release: GetReleaseAddr) -> Result<Self, String> {
+        let lock = exports::lock_host();
+        exports::reset_state();
+
+        let imguictx = imgui_context();
+        // arcdps passes its module handle, which is only used to look up exports
+        let arc_dll = std::ptr::dangling_mut::<c_void>();
+        let load = init(
+            ARC_VERSION.as_ptr() as _,
+            imguictx,
+            null_mut(),
+            arc_dll,
+            Some(imgui_malloc),
+            Some(imgui_free),
+        );
+        let export = &*load();
+        if export.sig == 0 {
+            let error = CStr::from_ptr(export.size as *const c_char);
+            return Err(error.to_string_lossy().into_owned());
+        }
+
+        Ok(Self {
+            export,
+            release: release(),
+            extras: None,
+            _lock: lock,
+        })
+    }
+
+    /// Returns the export table of the plugin.
+    pub fn export(&self) -> &ArcDpsExport {
+        self.export
+    }
+
+    /// Returns the name of the plugin.
+    pub fn name(&self) -> &str {
+        unsafe { CStr::from_ptr(self.export.out_name as _) }
+            .to_str()
+            .unwrap_or_default()
+    }
+
+    /// Returns the signature of the plugin.
+    pub fn sig(&self) -> u32 {
+        self.export.sig
+    }
+
+    /// Returns the lines the plugin logged to the arcdps log file (`e3`).
+    pub fn file_log(&self) -> Vec<String> {
+        exports::with_state(|state| state.file_log.clone())
+    }
+
+    /// Returns the lines the plugin logged to the arcdps log window (`e8`).
+    pub fn window_log(&self) -> Vec<String> {
+        exports::with_state(|state| state.window_log.clone())
+    }
+
+    /// Returns the events the plugin added with `e9` together with the
+    /// signature that was passed.
+    pub fn added_events(&self) -> Vec<(CombatEvent, u32)> {
+        exports::with_state(|state| state.events.clone())
+    }
+
+    /// Returns the events the plugin added with `e10` together with the
+    /// signature that was passed.
+    pub fn added_local_events(&self) -> Vec<(CombatEvent, u32)> {
+        exports::with_state(|state| state.local_events.clone())
+    }
+
+    /// Sets the path returned by `e0`.
+    pub fn set_ini_path(&self, path: &str) {
+        let path = path.encode_utf16().chain([0]).collect();
+        exports::with_state(|state| state.ini_path = path);
+    }
+
+    /// Sets the value returned by `e6`.
+    pub fn set_ui_settings(&self, ui_settings: u64) {
+        exports::with_state(|state| state.ui_settings = ui_settings);
+    }
+
+    /// Sets the value returned by `e7`.
+    pub fn set_modifiers(&self, modifiers: u64) {
+        exports::with_state(|state| state.modifiers = modifiers);
+    }
+
+    /// Sets the 5 color tables returned by `e5`: the core colors, the base
+    /// and highlight colors of the professions and the base and highlight
+    /// colors of the subgroups.
+    pub fn set_colors(&self, colors: [[[f32; 4]; 16]; 5]) {
+        exports::with_state(|state| state.colors = colors);
+    }
+
+    /// Calls the `combat` callback of the plugin.
+    /// Skill names are kept alive for the lifetime of the process, like arcdps
+    /// does.
+    pub fn combat(
+        &self,
+        ev: Option<&CombatEvent>,
+        src: Option<&AgentOwned>,
+        dst: Option<&AgentOwned>,
+        skill_name: Option<&str>,
+        id: u64,
+        revision: u64,
+    ) {
+        if let Some(combat) = self.export.combat {
+            call_combat(combat, ev, src, dst, skill_name, id, revision);
+        }
+    }
+
+    /// Calls the `combat_local` callback of the plugin.
+    /// See [Host::combat].
+    pub fn combat_local(
+        &self,
+        ev: Option<&CombatEvent>,
+        src: Option<&AgentOwned>,
+        dst: Option<&AgentOwned>,
+        skill_name: Option<&str>,
+        id: u64,
+        revision: u64,
+    ) {
+        if let Some(combat) = self.export.combat_local {
+            call_combat(combat, ev, src, dst, skill_name, id, revision);
+        }
+    }
+
+    /// Replays a log into the `combat` and `combat_local` callbacks of the
+    /// plugin. See [Replayer](arcdps::evtc::Replayer).
+    #[cfg(feature = "evtc")]
+    pub fn replay(&self, evtc: &arcdps::evtc::Evtc, speed: arcdps::evtc::ReplaySpeed) {
+        // SAFETY: the callbacks are the ones the plugin exported
+        unsafe {
+            arcdps::evtc::Replayer::new(evtc)
+                .speed(speed)
+                .replay(self.export.combat, self.export.combat_local)
+        }
+    }
+
+    /// Renders one imgui frame and calls the `imgui` callback of the plugin.
+    pub fn imgui(&self, not_character_select_or_loading: bool) {
+        if let Some(imgui) = self.export.imgui {
+            with_frame(|| unsafe { imgui(not_character_select_or_loading as u32) });
+        }
+    }
+
+    /// Renders one imgui frame and calls the `options_end` callback of the
+    /// plugin.
+    pub fn options_end(&self) {
+        if let Some(options_end) = self.export.options_end {
+            with_frame(|| unsafe { options_end() });
+        }
+    }
+
+    /// Renders one imgui frame and calls the `options_windows` callback of the
+    /// plugin. Returns true if the plugin wants to hide the checkbox.
+    pub fn options_windows(&self, window_name: Option<&str>) -> bool {
+        let Some(options_windows) = self.export.options_windows else {
+            return false;
+        };
+        let window_name = window_name.map(|name| CString::new(name).unwrap());
+        let window_name = window_name
+            .as_ref()
+            .map_or(null_mut(), |name| name.as_ptr() as _);
+        with_frame(|| unsafe { options_windows(window_name) })
+    }
+
+    /// Calls the `wnd_filter` callback of the plugin with a raw window
+    /// message. Returns the message that gets passed on, 0 means it was
+    /// consumed.
+    pub fn wnd_filter(&self, u_msg: u32, w_param: usize, l_param: isize) -> u32 {
+        match self.export.wnd_filter {
+            Some(wnd) => unsafe { wnd(null_mut(), u_msg, w_param, l_param) },
+            None => u_msg,
+        }
+    }
+
+    /// Calls the `wnd_nofilter` callback of the plugin with a raw window
+    /// message. See [Host::wnd_filter].
+    pub fn wnd_nofilter(&self, u_msg: u32, w_param: usize, l_param: isize) -> u32 {
+        match self.export.wnd_nofilter {
+            Some(wnd) => unsafe { wnd(null_mut(), u_msg, w_param, l_param) },
+            None => u_msg,
+        }
+    }
+
+    /// Sends a key press or release of a virtual key to the `wnd_filter`
+    /// callback. Returns true if the key press is passed on to the game.
+    pub fn key_filter(&self, key: usize, key_down: bool, prev_key_down: bool) -> bool {
+        let (u_msg, l_param) = key_message(key_down, prev_key_down);
+        self.wnd_filter(u_msg, key, l_param) != 0
+    }
+
+    /// Calls `arcdps_unofficial_extras_subscriber_init` of the plugin the same
+    /// way unofficial extras does it. Returns true if the plugin subscribed.
+    ///
+    /// # Safety
+    /// The function must be the one generated by
+    /// [arcdps_export!](arcdps::arcdps_export).
+    pub unsafe fn init_extras(
+        &mut self,
+        init: ExtrasSubscriberInit,
+        account_name: &str,
+        version: &'static CStr,
+    ) -> bool {
+        let account_name = CString::new(format!(":{account_name}")).unwrap();
+        let addon = RawExtrasAddonInfo {
+            api_version: 2,
+            max_info_version: 3,
+            string_version: version.as_ptr() as _,
+            self_account_name: account_name.as_ptr() as _,
+            extras_handle: null_mut(),
+        };
+        // all fields are integers, pointers or optional function pointers
+        let mut sub =
+            Box::new(MaybeUninit::<RawExtrasSubscriberInfo<InfoV3>>::zeroed().assume_init());
+        init(
+            &addon,
+            (&mut *sub as *mut RawExtrasSubscriberInfo<InfoV3>).cast(),
+        );
+
+        let subscribed = sub.header.info_version != 0;
+        self.extras = subscribed.then_some(sub);
+        subscribed
+    }
+
+    /// Calls the squad update callback of unofficial extras.
+    pub fn extras_squad_update(&self, users: &[UserInfoOwned]) {
+        let Some(cb) = self.extras().and_then(|info| info.squad_update_callback) else {
+            return;
+        };
+        let names: Vec<_> = users
+            .iter()
+            .map(|user| {
+                user.account_name
+                    .as_ref()
+                    .map(|name| CString::new(format!(":{name}")).unwrap())
+            })
+            .collect();
+        let raw: Vec<_> = users
+            .iter()
+            .zip(&names)
+            .map(|(user, name)| RawUserInfo {
+                account_name: name.as_ref().map_or(std::ptr::null(), |n| n.as_ptr() as _),
+                join_time: user.join_time,
+                role: user.role,
+                subgroup: user.subgroup,
+                ready_status: user.ready_status,
+            })
+            .collect();
+        unsafe { cb(raw.as_ptr(), raw.len() as u64) }
+    }
+
+    /// Calls the language changed callback of unofficial extras.
+    pub fn extras_language_changed(&self, language: Language) {
+        if let Some(cb) = self
+            .extras()
+            .and_then(|info| info.language_changed_callback)
+        {
+            unsafe { cb(language) }
+        }
+    }
+
+    /// Calls the keybind changed callback of unofficial extras.
+    pub fn extras_keybind_changed(&self, changed: RawKeyBindChanged) {
+        if let Some(cb) = self
+            .extras()
+            .and_then(|info| info.key_bind_changed_callback)
+        {
+            unsafe { cb(changed) }
+        }
+    }
+
+    /// Calls the chat message callback of unofficial extras.
+    pub fn extras_chat_message(&self, msg: &SquadMessageInfo) {
+        if let Some(cb) = self.extras().and_then(|info| info.chat_message_callback) {
+            let msg = OwnedSquadMessage::new(msg);
+            unsafe { cb(&msg.raw) }
+        }
+    }
+
+    /// Calls the second chat message callback of unofficial extras.
+    pub fn extras_chat_message2(&self, msg: &ChatMessageInfo2) {
+        let Some(cb) = self.extras().and_then(|info| info.chat_message_callback2) else {
+            return;
+        };
+        match msg {
+            ChatMessageInfo2::Squad(msg) => {
+                let msg = OwnedSquadMessage::new(msg);
+                let raw = RawChatMessageInfo2 {
+                    squad_message_info: &msg.raw,
+                };
+                unsafe { cb(ChatMessageType::Squad, raw) }
+            }
+            ChatMessageInfo2::Npc(msg) => {
+                let msg = OwnedNpcMessage::new(msg);
+                let raw = RawChatMessageInfo2 {
+                    npc_message_info: &msg.raw,
+                };
+                unsafe { cb(ChatMessageType::NPC, raw) }
+            }
+            _ => {}
+        }
+    }
+
+    fn extras(&self) -> Option<&InfoV3> {
+        self.extras.as_deref().map(|sub| &sub.content)
+    }
+}
+
+impl Drop for Host {
+    fn drop(&mut self) {
+        unsafe { (self.release)() }
+    }
+}
+
+fn key_message(key_down: bool, prev_key_down: bool) -> (u32, isize) {
+    let u_msg = if key_down { WM_KEYDOWN } else { WM_KEYUP };
+    let l_param = (prev_key_down as isize) << 30;
+    (u_msg, l_param)
+}
+
+fn raw_agent(agent: &AgentOwned) -> (Option<CString>, RawAgent) {
+    let name = agent
+        .name
+        .as_ref()
+        .map(|n| CString::new(n.as_str()).unwrap());
+    let raw = RawAgent {
+        name: name.as_ref().map_or(null_mut(), |n| n.as_ptr() as _),
+        id: agent.id,
+        prof: agent.prof,
+        elite: agent.elite,
+        self_: agent.self_,
+        team: agent.team,
+    };
+    (name, raw)
+}
+
+fn call_combat(
+    combat: arcdps::RawCombatCallback,
+    ev: Option<&CombatEvent>,
+    src: Option<&AgentOwned>,
+    dst: Option<&AgentOwned>,
+    skill_name: Option<&str>,
+    id: u64,
+    revision: u64,
+) {
+    let src = src.map(raw_agent);
+    let dst = dst.map(raw_agent);
+    let skill_name = skill_name.map_or(null_mut(), |name| {
+        arcdps::helpers::intern_skill_name(name).as_ptr() as _
+    });
+    unsafe {
+        combat(
+            ev,
+            src.as_ref().map(|(_, raw)| raw),
+            dst.as_ref().map(|(_, raw)| raw),
+            skill_name,
+            id,
+            revision,
+        )
+    }
+}
+
+/// Keeps the strings of a [RawSquadMessageInfo] alive.
+struct OwnedSquadMessage {
+    _strings: [CString; 4],
+    raw: RawSquadMessageInfo,
+}
+
+impl OwnedSquadMessage {
+    fn new(msg: &SquadMessageInfo) -> Self {
+        let timestamp = msg
+            .timestamp
+            .to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+        let strings = [
+            CString::new(timestamp).unwrap(),
+            CString::new(format!(":{}", msg.account_name)).unwrap(),
+            CString::new(msg.character_name).unwrap(),
+            CString::new(msg.text).unwrap(),
+        ];
+        let raw = RawSquadMessageInfo {
+            channel_id: msg.channel_id,
+            channel_type: msg.channel_type,
+            subgroup: msg.subgroup,
+            is_broadcast: msg.is_broadcast as u8,
+            timestamp: strings[0].as_ptr() as _,
+            timestamp_length: strings[0].as_bytes().len() as u64,
+            account_name: strings[1].as_ptr() as _,
+            account_name_length: strings[1].as_bytes().len() as u64,
+            character_name: strings[2].as_ptr() as _,
+            character_name_length: strings[2].as_bytes().len() as u64,
+            text: strings[3].as_ptr() as _,
+            text_length: strings[3].as_bytes().len() as u64,
+        };
+        Self {
+            _strings: strings,
+            raw,
+        }
+    }
+}
+
+/// Keeps the strings of a [RawNpcMessageInfo] alive.
+struct OwnedNpcMessage {
+    _strings: [CString; 2],
+    raw: RawNpcMessageInfo,
+}
+
+impl OwnedNpcMessage {
+    fn new(msg: &NpcMessageInfo) -> Self {
+        let strings = [
+            CString::new(msg.character_name).unwrap(),
+            CString::new(msg.message).unwrap(),
+        ];
+        let raw = RawNpcMessageInfo {
+            character_name: strings[0].as_ptr() as _,
+            character_name_length: strings[0].as_bytes().len() as u64,
+            message: strings[1].as_ptr() as _,
+            message_length: strings[1].as_bytes().len() as u64,
+            timestamp: msg.timestamp.timestamp_nanos_opt().unwrap_or_default() as u64,
+        };
+        Self {
+            _strings: strings,
+            raw,
+        }
+    }
+}
+
+extern "C" {
+    fn malloc(size: usize) -> *mut c_void;
+    fn free(ptr: *mut c_void);
+}
+
+/// The plugin replaces the allocator of imgui with the one passed by arcdps.
+unsafe extern "C" fn imgui_malloc(size: usize, _user_data: *mut c_void) -> *mut c_void {
+    malloc(size)
+}
+
+unsafe extern "C" fn imgui_free(ptr: *mut c_void, _user_data: *mut c_void) {
+    free(ptr)
+}
+
+/// Returns the imgui context that gets passed to the plugin.
+/// The plugin keeps using the first context it received, so all hosts share
+/// one context.
+#[cfg(feature = "imgui")]
+unsafe fn imgui_context() -> *mut ImguiContext {
+    use std::sync::OnceLock;
+
+    use arcdps::imgui::sys;
+
+    static CONTEXT: OnceLock<usize> = OnceLock::new();
+    *CONTEXT.get_or_init(|| {
+        let ctx = sys::igCreateContext(null_mut());
+        sys::igSetCurrentContext(ctx);
+        let io = &mut *sys::igGetIO();
+        io.IniFilename = std::ptr::null();
+        io.DisplaySize = sys::ImVec2 {
+            x: 1920.0,
+            y: 1080.0,
+        };
+        io.DeltaTime = 1.0 / 60.0;
+        let mut pixels = null_mut();
+        let (mut width, mut height) = (0, 0);
+        sys::ImFontAtlas_GetTexDataAsRGBA32(
+            io.Fonts,
+            &mut pixels,
+            &mut width,
+            &mut height,
+            null_mut(),
+        );
+        ctx as usize
+    }) as *mut ImguiContext
+}
+
+#[cfg(not(feature = "imgui"))]
+unsafe fn imgui_context() -> *mut ImguiContext {
+    null_mut()
+}
+
+#[cfg(feature = "imgui")]
+fn with_frame<R>(f: impl FnOnce() -> R) -> R {
+    use arcdps::imgui::sys;
+
+    unsafe { sys::igNewFrame() };
+    let res = f();
+    unsafe { sys::igRender() };
+    res
+}
+
+#[cfg(not(feature = "imgui"))]
+fn with_frame<R>(f: impl FnOnce() -> R) -> R {
+    f()
+}
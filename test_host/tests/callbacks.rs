@@ -0,0 +1,318 @@
+use std::sync::Mutex;
+
+use arcdps::{
+    Agent, AgentOwned, ChannelType, CombatEvent, CombatEventBuilder, DeviceType, KeyBindChanged,
+    KeyControl, Language, RawKey, RawKeyBindChanged, SquadMessageInfo, UserInfoIter, UserInfoOwned,
+    UserRole,
+};
+use arcdps_test_host::Host;
+use chrono::DateTime;
+
+arcdps::arcdps_export! {
+    name: "callbacks",
+    sig: 0x1234,
+    combat: crate::combat,
+    combat_local: crate::combat_local,
+    imgui: crate::imgui,
+    options_end: crate::options_end,
+    options_windows: crate::options_windows,
+    wnd_filter: crate::wnd_filter,
+    wnd_nofilter: crate::wnd_nofilter,
+    unofficial_extras_init: crate::extras_init,
+    unofficial_extras_squad_update: crate::squad_update,
+    unofficial_extras_language_changed: crate::language_changed,
+    unofficial_extras_keybind_changed: crate::keybind_changed,
+    unofficial_extras_chat_message: crate::chat_message,
+}
+
+/// What the callbacks of the plugin were called with.
+static CALLS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+fn record(call: String) {
+    CALLS.lock().unwrap().push(call);
+}
+
+fn load() -> Host {
+    let host = arcdps_test_host::load!().unwrap();
+    CALLS.lock().unwrap().clear();
+    host
+}
+
+fn calls() -> Vec<String> {
+    std::mem::take(&mut CALLS.lock().unwrap())
+}
+
+fn combat(
+    ev: Option<&CombatEvent>,
+    src: Option<Agent>,
+    _dst: Option<Agent>,
+    skill_name: Option<&'static str>,
+    id: u64,
+    _revision: u64,
+) {
+    record(format!(
+        "combat {id} {:?} {:?} {skill_name:?}",
+        ev.map(|ev| ev.value),
+        src.and_then(|src| src.name)
+    ));
+}
+
+fn combat_local(
+    ev: Option<&CombatEvent>,
+    src: Option<Agent>,
+    _dst: Option<Agent>,
+    skill_name: Option<&'static str>,
+    id: u64,
+    _revision: u64,
+) {
+    record(format!(
+        "combat_local {id} {:?} {:?} {skill_name:?}",
+        ev.map(|ev| ev.value),
+        src.and_then(|src| src.name)
+    ));
+}
+
+#[cfg(feature = "imgui")]
+fn imgui(ui: &arcdps::imgui::Ui, not_character_select_or_loading: bool) {
+    ui.text("imgui");
+    record(format!("imgui {not_character_select_or_loading}"));
+}
+
+#[cfg(not(feature = "imgui"))]
+fn imgui(not_character_select_or_loading: bool) {
+    record(format!("imgui {not_character_select_or_loading}"));
+}
+
+#[cfg(feature = "imgui")]
+fn options_end(ui: &arcdps::imgui::Ui) {
+    ui.text("options");
+    record("options_end".to_string());
+}
+
+#[cfg(not(feature = "imgui"))]
+fn options_end() {
+    record("options_end".to_string());
+}
+
+#[cfg(feature = "imgui")]
+fn options_windows(_ui: &arcdps::imgui::Ui, window_name: Option<&str>) -> bool {
+    record(format!("options_windows {window_name:?}"));
+    window_name == Some("hidden")
+}
+
+#[cfg(not(feature = "imgui"))]
+fn options_windows(window_name: Option<&str>) -> bool {
+    record(format!("options_windows {window_name:?}"));
+    window_name == Some("hidden")
+}
+
+/// Consumes the key `F`.
+fn wnd_filter(key: usize, key_down: bool, prev_key_down: bool) -> bool {
+    record(format!("wnd_filter {key} {key_down} {prev_key_down}"));
+    key != 'F' as usize
+}
+
+fn wnd_nofilter(key: usize, key_down: bool, prev_key_down: bool) -> bool {
+    record(format!("wnd_nofilter {key} {key_down} {prev_key_down}"));
+    true
+}
+
+fn extras_init(account_name: Option<&str>, version: Option<&'static str>) {
+    record(format!("extras_init {account_name:?} {version:?}"));
+}
+
+fn squad_update(users: UserInfoIter) {
+    for user in users {
+        record(format!(
+            "squad_update {:?} {} {:?}",
+            user.account_name, user.subgroup, user.role
+        ));
+    }
+}
+
+fn language_changed(language: Language) {
+    record(format!("language_changed {language:?}"));
+}
+
+fn keybind_changed(changed: KeyBindChanged) {
+    record(format!(
+        "keybind_changed {:?} {} {:?}",
+        changed.key_control, changed.secondary, changed.key.input
+    ));
+}
+
+fn chat_message(msg: &SquadMessageInfo) {
+    record(format!(
+        "chat_message {} {}: {}",
+        msg.account_name, msg.character_name, msg.text
+    ));
+}
+
+fn agent(name: &str) -> AgentOwned {
+    AgentOwned {
+        name: Some(name.to_string()),
+        id: 1,
+        prof: 1,
+        elite: 0,
+        self_: 1,
+        team: 0,
+    }
+}
+
+#[test]
+fn combat_and_combat_local() {
+    let host = load();
+    let ev = CombatEventBuilder::new().value(5).build();
+
+    host.combat(Some(&ev), None, None, Some("Skill"), 1, 1);
+    host.combat_local(None, Some(&agent("Local")), None, None, 2, 1);
+    host.combat_local(Some(&ev), None, None, None, 3, 1);
+
+    assert_eq!(
+        calls(),
+        [
+            "combat 1 Some(5) None Some(\"Skill\")",
+            "combat_local 2 None Some(\"Local\") None",
+            "combat_local 3 Some(5) None None",
+        ]
+    );
+}
+
+#[test]
+fn key_filter() {
+    let host = load();
+
+    assert!(host.key_filter('A' as usize, true, false));
+    assert!(!host.key_filter('F' as usize, true, true));
+    assert!(!host.key_filter('F' as usize, false, true));
+
+    assert_eq!(
+        calls(),
+        [
+            "wnd_filter 65 true false",
+            "wnd_filter 70 true true",
+            "wnd_filter 70 false true",
+        ]
+    );
+}
+
+#[test]
+fn wnd_filter_passes_other_messages() {
+    const WM_MOUSEMOVE: u32 = 0x200;
+
+    let host = load();
+
+    assert_eq!(host.wnd_filter(WM_MOUSEMOVE, 0, 0), WM_MOUSEMOVE);
+    assert_eq!(
+        host.wnd_nofilter(arcdps::WM_KEYUP, 'F' as usize, 1 << 30),
+        arcdps::WM_KEYUP
+    );
+
+    assert_eq!(calls(), ["wnd_nofilter 70 false true"]);
+}
+
+#[test]
+fn ui() {
+    let host = load();
+
+    host.imgui(true);
+    host.options_end();
+    assert!(!host.options_windows(Some("squad")));
+    assert!(host.options_windows(Some("hidden")));
+    assert!(!host.options_windows(None));
+
+    assert_eq!(
+        calls(),
+        [
+            "imgui true",
+            "options_end",
+            "options_windows Some(\"squad\")",
+            "options_windows Some(\"hidden\")",
+            "options_windows None",
+        ]
+    );
+}
+
+#[test]
+fn extras() {
+    let mut host = load();
+
+    // SAFETY: the function is generated by arcdps_export!
+    let subscribed = unsafe {
+        host.init_extras(
+            __arcdps_gen_export::arcdps_unofficial_extras_subscriber_init,
+            "Account.1234",
+            c"1.0",
+        )
+    };
+    assert!(subscribed);
+
+    host.extras_squad_update(&[UserInfoOwned {
+        account_name: Some("Other.5678".to_string()),
+        join_time: 1,
+        role: UserRole::Member,
+        subgroup: 2,
+        ready_status: false,
+    }]);
+    host.extras_language_changed(Language::German);
+    host.extras_keybind_changed(RawKeyBindChanged {
+        key_control: KeyControl::Movement_MoveForward as i32,
+        key_index: 1,
+        single_key: RawKey {
+            device_type: DeviceType::Keyboard as i32,
+            code: 1000,
+            modifier: 0,
+        },
+    });
+    host.extras_chat_message(&SquadMessageInfo {
+        channel_id: 1,
+        channel_type: ChannelType::Squad,
+        subgroup: 2,
+        is_broadcast: false,
+        timestamp: DateTime::UNIX_EPOCH,
+        account_name: "Other.5678",
+        character_name: "Other",
+        text: "hi",
+    });
+
+    assert_eq!(
+        calls(),
+        [
+            "extras_init Some(\"Account.1234\") Some(\"1.0\")",
+            "squad_update Some(\"Other.5678\") 2 Member",
+            "language_changed German",
+            "keybind_changed Movement_MoveForward true Unknown { device_type: 2, code: 1000 }",
+            "chat_message Other.5678 Other: hi",
+        ]
+    );
+}
+
+#[test]
+fn captures_log() {
+    let host = load();
+
+    log::info!("hello");
+    log::warn!("world");
+
+    let file_log = host.file_log();
+    assert_eq!(file_log.len(), 2);
+    assert!(file_log[0].starts_with("callbacks - "));
+    assert!(file_log[0].ends_with("INFO: hello"));
+    assert!(file_log[1].ends_with("WARN: world"));
+    assert_eq!(host.window_log(), file_log);
+}
+
+#[test]
+fn ui_only_on_render_thread() {
+    let host = load();
+    let imgui = host.export().imgui.unwrap();
+
+    host.imgui(false);
+    // SAFETY: the callback is the one the plugin exported
+    std::thread::spawn(move || unsafe { imgui(1) })
+        .join()
+        .unwrap();
+    host.imgui(true);
+
+    assert_eq!(calls(), ["imgui false", "imgui true"]);
+}
@@ -0,0 +1,54 @@
+use arcdps::{ArcColors, Profession};
+
+arcdps::arcdps_export! {
+    name: "colors",
+    sig: 0x1234,
+}
+
+/// A color that encodes its table and index.
+fn color(table: usize, index: usize) -> [f32; 4] {
+    [table as f32, index as f32, 0.5, 1.0]
+}
+
+#[test]
+fn reads_colors() {
+    assert_eq!(ArcColors::read(), None);
+
+    let host = arcdps_test_host::load!().unwrap();
+    let mut tables = [[[0.0; 4]; 16]; 5];
+    for (table, colors) in tables.iter_mut().enumerate() {
+        for (index, entry) in colors.iter_mut().enumerate() {
+            *entry = color(table, index);
+        }
+    }
+    host.set_colors(tables);
+
+    let colors = ArcColors::read().unwrap();
+    assert_eq!(colors.core.transparent, color(0, 0));
+    assert_eq!(colors.core.white, color(0, 1));
+    assert_eq!(colors.core.light_teal, color(0, 7));
+    assert_eq!(colors.core.dark_grey, color(0, 9));
+    assert_eq!(colors.profession_base[9], color(1, 9));
+    assert_eq!(colors.profession_highlight[0], color(2, 0));
+    assert_eq!(colors.subgroup_base[15], color(3, 15));
+    assert_eq!(colors.subgroup_highlight[1], color(4, 1));
+
+    assert_eq!(colors.profession_color(Profession::Mesmer), color(1, 7));
+    assert_eq!(
+        colors.profession_highlight_color(Profession::Revenant),
+        color(2, 9)
+    );
+    assert_eq!(
+        colors.profession_color(Profession::Unknown(42)),
+        color(1, 0)
+    );
+    assert_eq!(
+        colors.profession_highlight_color(Profession::Unknown(42)),
+        color(2, 0)
+    );
+
+    assert_eq!(colors.subgroup_color(2), Some(color(3, 2)));
+    assert_eq!(colors.subgroup_highlight_color(15), Some(color(4, 15)));
+    assert_eq!(colors.subgroup_color(16), None);
+    assert_eq!(colors.subgroup_highlight_color(u8::MAX), None);
+}
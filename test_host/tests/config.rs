@@ -0,0 +1,34 @@
+use std::path::Path;
+
+use arcdps::{arcdps_config_dir, arcdps_ini_path, IniPathError};
+
+arcdps::arcdps_export! {
+    name: "config",
+    sig: 0x1234,
+}
+
+#[test]
+fn ini_path() {
+    assert_eq!(arcdps_ini_path(), Err(IniPathError::NotLoaded));
+
+    {
+        let host = arcdps_test_host::load!().unwrap();
+        assert_eq!(arcdps_ini_path(), Err(IniPathError::NullPath));
+        assert_eq!(arcdps_config_dir(), Err(IniPathError::NullPath));
+
+        host.set_ini_path("arcdps.ini");
+        assert_eq!(arcdps_config_dir(), Err(IniPathError::NoParent));
+
+        host.set_ini_path("C:/Guild Wars 2/addons/arcdps/arcdps.ini");
+        assert_eq!(
+            arcdps_ini_path().unwrap(),
+            Path::new("C:/Guild Wars 2/addons/arcdps/arcdps.ini")
+        );
+        assert_eq!(
+            arcdps_config_dir().unwrap(),
+            Path::new("C:/Guild Wars 2/addons/arcdps")
+        );
+    }
+
+    assert_eq!(arcdps_config_dir(), Err(IniPathError::NotLoaded));
+}
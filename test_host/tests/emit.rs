@@ -0,0 +1,39 @@
+use arcdps::{emit_area, emit_local, CombatEventBuilder, StateChange};
+
+arcdps::arcdps_export! {
+    name: "emit",
+    sig: 0x1234,
+}
+
+#[test]
+fn emits_signed_events() {
+    let ev = |time| {
+        CombatEventBuilder::new()
+            .time(time)
+            .src_agent(2)
+            .state_change(StateChange::Extension)
+            .build()
+    };
+
+    {
+        let host = arcdps_test_host::load!().unwrap();
+        assert!(emit_area(&ev(1)));
+        assert!(emit_local(&ev(2)));
+        assert!(emit_local(&ev(3)));
+
+        let area = host.added_events();
+        assert_eq!(area.len(), 1);
+        assert_eq!((area[0].0.time, area[0].1), (1, 0x1234));
+
+        let local: Vec<_> = host
+            .added_local_events()
+            .iter()
+            .map(|(ev, sig)| (ev.time, ev.src_agent, *sig))
+            .collect();
+        assert_eq!(local, [(2, 2, 0x1234), (3, 2, 0x1234)]);
+    }
+
+    // the host was dropped and released the plugin
+    assert!(!emit_area(&ev(4)));
+    assert!(!emit_local(&ev(5)));
+}
@@ -0,0 +1,56 @@
+//! A panic poisons the plugin for the whole process, so this test has its own
+//! binary.
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use arcdps::{Agent, CombatEvent};
+
+arcdps::arcdps_export! {
+    name: "panic",
+    sig: 0x1234,
+    combat: crate::combat,
+    wnd_filter: crate::wnd_filter,
+}
+
+static CALLS: AtomicU32 = AtomicU32::new(0);
+
+fn combat(
+    ev: Option<&CombatEvent>,
+    _src: Option<Agent>,
+    _dst: Option<Agent>,
+    _skill_name: Option<&'static str>,
+    _id: u64,
+    _revision: u64,
+) {
+    CALLS.fetch_add(1, Ordering::Relaxed);
+    if ev.is_some_and(|ev| ev.value == 13) {
+        panic!("unlucky");
+    }
+}
+
+fn wnd_filter(_key: usize, _key_down: bool, _prev_key_down: bool) -> bool {
+    CALLS.fetch_add(1, Ordering::Relaxed);
+    false
+}
+
+#[test]
+fn panic_poisons_plugin() {
+    let host = arcdps_test_host::load!().unwrap();
+    let ev = |value| arcdps::CombatEventBuilder::new().value(value).build();
+
+    host.combat(Some(&ev(1)), None, None, None, 1, 1);
+    assert!(!host.key_filter('F' as usize, true, false));
+    assert!(!arcdps::is_poisoned());
+
+    host.combat(Some(&ev(13)), None, None, None, 2, 1);
+    assert!(arcdps::is_poisoned());
+    assert_eq!(CALLS.load(Ordering::Relaxed), 3);
+
+    let log = host.file_log();
+    assert_eq!(log.len(), 1);
+    assert!(log[0].ends_with("ERROR: combat panicked, skipping all further callbacks: unlucky"));
+
+    // the plugin is skipped and the key is passed on to the game
+    host.combat(Some(&ev(1)), None, None, None, 3, 1);
+    assert!(host.key_filter('F' as usize, true, false));
+    assert_eq!(CALLS.load(Ordering::Relaxed), 3);
+}
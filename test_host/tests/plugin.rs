@@ -0,0 +1,131 @@
+use std::{
+    error::Error,
+    ffi::c_void,
+    ptr::{null_mut, NonNull},
+    sync::{Mutex, OnceLock},
+};
+
+use arcdps::{Agent, CombatEvent, CombatEventBuilder, Language, RawCombatCallback};
+
+arcdps::arcdps_export! {
+    name: "plugin",
+    sig: 0x1234,
+    plugin: crate::Recorder,
+}
+
+/// What the plugin was called with.
+static CALLS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// The exported combat callback, to call it from within another callback.
+static COMBAT: OnceLock<RawCombatCallback> = OnceLock::new();
+
+fn record(call: String) {
+    CALLS.lock().unwrap().push(call);
+}
+
+fn calls() -> Vec<String> {
+    std::mem::take(&mut CALLS.lock().unwrap())
+}
+
+struct Recorder {
+    events: u32,
+}
+
+impl arcdps::Plugin for Recorder {
+    fn init(_swapchain: Option<NonNull<c_void>>) -> Result<Self, Box<dyn Error>> {
+        record("init".to_string());
+        Ok(Self { events: 0 })
+    }
+
+    fn release(&mut self) {
+        record(format!("release {}", self.events));
+    }
+
+    fn combat(
+        &mut self,
+        ev: Option<&CombatEvent>,
+        _src: Option<Agent>,
+        _dst: Option<Agent>,
+        _skill_name: Option<&'static str>,
+        id: u64,
+        _revision: u64,
+    ) {
+        self.events += 1;
+        record(format!("combat {id} {:?}", ev.map(|ev| ev.value)));
+    }
+
+    fn combat_local(
+        &mut self,
+        _ev: Option<&CombatEvent>,
+        _src: Option<Agent>,
+        _dst: Option<Agent>,
+        _skill_name: Option<&'static str>,
+        id: u64,
+        _revision: u64,
+    ) {
+        record(format!("combat_local {id}"));
+    }
+
+    fn imgui(&mut self, _ui: &arcdps::imgui::Ui, _not_character_select_or_loading: bool) {
+        record("imgui".to_string());
+        // a combat event that arrives while the frame is drawn
+        if let Some(combat) = COMBAT.get() {
+            let ev = CombatEventBuilder::new().build();
+            // SAFETY: the callback is the one the plugin exported
+            unsafe { combat(Some(&ev), None, None, null_mut(), 9, 1) };
+        }
+    }
+
+    fn options_windows(&mut self, _ui: &arcdps::imgui::Ui, window_name: Option<&str>) -> bool {
+        record(format!("options_windows {window_name:?}"));
+        true
+    }
+
+    fn wnd_filter(&mut self, key: usize, _key_down: bool, _prev_key_down: bool) -> bool {
+        record(format!("wnd_filter {key}"));
+        false
+    }
+
+    fn unofficial_extras_language_changed(&mut self, language: Language) {
+        record(format!("language_changed {language:?}"));
+    }
+}
+
+#[test]
+fn forwards_callbacks() {
+    let mut host = arcdps_test_host::load!().unwrap();
+    let ev = CombatEventBuilder::new().value(3).build();
+
+    host.combat(Some(&ev), None, None, None, 1, 1);
+    host.combat_local(None, None, None, None, 2, 1);
+    assert!(host.options_windows(Some("squad")));
+    assert!(!host.key_filter('F' as usize, true, false));
+    // SAFETY: the function is generated by arcdps_export!
+    unsafe {
+        host.init_extras(
+            __arcdps_gen_export::arcdps_unofficial_extras_subscriber_init,
+            "Account.1234",
+            c"1.0",
+        )
+    };
+    host.extras_language_changed(Language::French);
+
+    // the event from within the frame is skipped instead of deadlocking
+    COMBAT.set(host.export().combat.unwrap()).unwrap();
+    host.imgui(true);
+    drop(host);
+
+    assert_eq!(
+        calls(),
+        [
+            "init",
+            "combat 1 Some(3)",
+            "combat_local 2",
+            "options_windows Some(\"squad\")",
+            "wnd_filter 70",
+            "language_changed French",
+            "imgui",
+            "release 1",
+        ]
+    );
+}